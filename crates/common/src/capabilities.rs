@@ -10,17 +10,17 @@ pub fn capabilities() -> Capabilities {
             variables: Some(LeafCapability {}),
             explain: Some(LeafCapability {}),
             nested_fields: NestedFieldCapabilities {
-                filter_by: None,
-                order_by: None,
-                aggregates: None,
+                filter_by: Some(LeafCapability {}),
+                order_by: Some(LeafCapability {}),
+                aggregates: Some(LeafCapability {}),
             },
             exists: ExistsCapabilities {
-                nested_collections: None,
+                nested_collections: Some(LeafCapability {}),
             },
         },
         mutation: MutationCapabilities {
             transactional: None,
-            explain: None,
+            explain: Some(LeafCapability {}),
         },
         relationships: Some(RelationshipCapabilities {
             relation_comparisons: Some(LeafCapability {}),
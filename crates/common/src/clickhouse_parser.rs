@@ -2,7 +2,7 @@ pub mod datatype;
 pub mod parameterized_query;
 use self::datatype::{
     AggregateFunctionDefinition, AggregateFunctionParameter, ClickHouseDataType as DT, Identifier,
-    SingleQuotedString,
+    JsonTypeParameter, SingleQuotedString,
 };
 
 use self::parameterized_query::{
@@ -57,6 +57,15 @@ peg::parser! {
         / r#enum()
         / aggregate_function()
         / simple_aggregate_function()
+        / json()
+        / variant()
+        / dynamic()
+        / point()
+        / ring()
+        / line_string()
+        / multi_line_string()
+        / polygon()
+        / multi_polygon()
         / nothing()
     rule nullable() -> DT = i("Nullable(") t:data_type() ")" { DT::Nullable(Box::new(t)) }
     rule uint8() -> DT = i("UInt8") { DT::UInt8 }
@@ -99,8 +108,26 @@ peg::parser! {
     rule r#enum() -> DT = i("Enum") ("8" / "16")?  "(" e:((n:single_quoted_string_value() i:(_ "=" _ i:integer_value() { i })? { (n, i) }) ** comma_separator()) ")" { DT::Enum(e)}
     rule aggregate_function() -> DT = i("AggregateFunction(") f:aggregate_function_definition() comma_separator() a:(data_type() ** comma_separator()) ")" { DT::AggregateFunction { function: f, arguments:  a }}
     rule simple_aggregate_function() -> DT =  i("SimpleAggregateFunction(") f:aggregate_function_definition() comma_separator() a:(data_type() ** comma_separator()) ")" { DT::SimpleAggregateFunction { function: f, arguments:  a }}
+    rule json() -> DT = i("JSON(") p:(json_type_parameter() ** comma_separator()) ")" { DT::Json(p) }
+        / i("JSON") { DT::Json(vec![]) }
+    rule variant() -> DT = i("Variant(") v:(data_type() ** comma_separator()) ")" { DT::Variant(v) }
+    rule dynamic() -> DT = i("Dynamic(") _ i("max_types") _ "=" _ n:integer_value() _ ")" { DT::Dynamic { max_types: Some(n) } }
+        / i("Dynamic") { DT::Dynamic { max_types: None } }
+    rule point() -> DT = i("Point") { DT::Point }
+    rule ring() -> DT = i("Ring") { DT::Ring }
+    rule line_string() -> DT = i("LineString") { DT::LineString }
+    rule multi_line_string() -> DT = i("MultiLineString") { DT::MultiLineString }
+    rule polygon() -> DT = i("Polygon") { DT::Polygon }
+    rule multi_polygon() -> DT = i("MultiPolygon") { DT::MultiPolygon }
     rule nothing() -> DT = i("Nothing") { DT::Nothing }
 
+    rule json_type_parameter() -> JsonTypeParameter = i("max_dynamic_paths") _ "=" _ n:integer_value() { JsonTypeParameter::MaxDynamicPaths(n) }
+        / i("max_dynamic_types") _ "=" _ n:integer_value() { JsonTypeParameter::MaxDynamicTypes(n) }
+        / i("SKIP") __ i("REGEXP") __ r:single_quoted_string_value() { JsonTypeParameter::SkipRegexp(r) }
+        / i("SKIP") __ p:json_path() { JsonTypeParameter::SkipPath(p) }
+        / p:json_path() __ t:data_type() { JsonTypeParameter::TypedPath { path: p, data_type: t } }
+    rule json_path() -> Vec<Identifier> = identifier() ++ "."
+
     rule aggregate_function_definition() -> AggregateFunctionDefinition = n:identifier() p:("(" p:(aggregate_function_parameter() ** comma_separator()) ")" { p })? { AggregateFunctionDefinition { name: n, parameters: p }}
     rule aggregate_function_parameter() -> AggregateFunctionParameter = s:single_quoted_string_value() { AggregateFunctionParameter::SingleQuotedString(s)}
         / f:floating_point_value() { AggregateFunctionParameter::FloatingPoint(f)}
@@ -196,6 +223,50 @@ fn can_parse_clickhouse_data_type() {
                 arguments: vec![DT::UInt64],
             },
         ),
+        ("JSON", DT::Json(vec![])),
+        (
+            "JSON(max_dynamic_paths=10, a.b UInt32, `c` Array(String), SKIP a.d, SKIP REGEXP 'e.*')",
+            DT::Json(vec![
+                JsonTypeParameter::MaxDynamicPaths(10),
+                JsonTypeParameter::TypedPath {
+                    path: vec![
+                        Identifier::Unquoted("a".to_string()),
+                        Identifier::Unquoted("b".to_string()),
+                    ],
+                    data_type: DT::UInt32,
+                },
+                JsonTypeParameter::TypedPath {
+                    path: vec![Identifier::BacktickQuoted("c".to_string())],
+                    data_type: DT::Array(Box::new(DT::String)),
+                },
+                JsonTypeParameter::SkipPath(vec![
+                    Identifier::Unquoted("a".to_string()),
+                    Identifier::Unquoted("d".to_string()),
+                ]),
+                JsonTypeParameter::SkipRegexp(SingleQuotedString("e.*".to_string())),
+            ]),
+        ),
+        (
+            "Variant(String, UInt64, Array(String))",
+            DT::Variant(vec![
+                DT::String,
+                DT::UInt64,
+                DT::Array(Box::new(DT::String)),
+            ]),
+        ),
+        ("Dynamic", DT::Dynamic { max_types: None }),
+        (
+            "Dynamic(max_types=16)",
+            DT::Dynamic {
+                max_types: Some(16),
+            },
+        ),
+        ("Point", DT::Point),
+        ("Array(Ring)", DT::Array(Box::new(DT::Ring))),
+        ("LineString", DT::LineString),
+        ("MultiLineString", DT::MultiLineString),
+        ("Polygon", DT::Polygon),
+        ("MultiPolygon", DT::MultiPolygon),
     ];
 
     for (s, t) in data_types {
@@ -85,6 +85,49 @@ impl Display for AggregateFunctionParameter {
     }
 }
 
+/// A parameter of the JSON data type, as in `JSON(max_dynamic_paths=10, a.b UInt32, SKIP a.c, SKIP REGEXP 'd.*')`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum JsonTypeParameter {
+    MaxDynamicPaths(u32),
+    MaxDynamicTypes(u32),
+    TypedPath {
+        path: Vec<Identifier>,
+        data_type: ClickHouseDataType,
+    },
+    SkipPath(Vec<Identifier>),
+    SkipRegexp(SingleQuotedString),
+}
+
+impl Display for JsonTypeParameter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fn write_path(f: &mut std::fmt::Formatter<'_>, path: &[Identifier]) -> std::fmt::Result {
+            let mut first = true;
+            for element in path {
+                if first {
+                    first = false;
+                } else {
+                    write!(f, ".")?;
+                }
+                write!(f, "{element}")?;
+            }
+            Ok(())
+        }
+        match self {
+            JsonTypeParameter::MaxDynamicPaths(n) => write!(f, "max_dynamic_paths={n}"),
+            JsonTypeParameter::MaxDynamicTypes(n) => write!(f, "max_dynamic_types={n}"),
+            JsonTypeParameter::TypedPath { path, data_type } => {
+                write_path(f, path)?;
+                write!(f, " {data_type}")
+            }
+            JsonTypeParameter::SkipPath(path) => {
+                write!(f, "SKIP ")?;
+                write_path(f, path)
+            }
+            JsonTypeParameter::SkipRegexp(regexp) => write!(f, "SKIP REGEXP {regexp}"),
+        }
+    }
+}
+
 /// A parsed representation of a clickhouse datatype string
 /// This should support the full scope of clickhouse types
 /// To create one from a string slice, use from_str()
@@ -153,6 +196,17 @@ pub enum ClickHouseDataType {
         function: AggregateFunctionDefinition,
         arguments: Vec<ClickHouseDataType>,
     },
+    Json(Vec<JsonTypeParameter>),
+    Variant(Vec<ClickHouseDataType>),
+    Dynamic {
+        max_types: Option<u32>,
+    },
+    Point,
+    Ring,
+    LineString,
+    MultiLineString,
+    Polygon,
+    MultiPolygon,
     Nothing,
 }
 
@@ -275,6 +329,49 @@ impl Display for ClickHouseDataType {
                 }
                 write!(f, ")")
             }
+            DT::Json(parameters) => {
+                write!(f, "JSON")?;
+                if !parameters.is_empty() {
+                    write!(f, "(")?;
+                    let mut first = true;
+                    for parameter in parameters {
+                        if first {
+                            first = false;
+                        } else {
+                            write!(f, ", ")?;
+                        }
+                        write!(f, "{parameter}")?;
+                    }
+                    write!(f, ")")?;
+                }
+                Ok(())
+            }
+            DT::Variant(variants) => {
+                write!(f, "Variant(")?;
+                let mut first = true;
+                for variant in variants {
+                    if first {
+                        first = false;
+                    } else {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{variant}")?;
+                }
+                write!(f, ")")
+            }
+            DT::Dynamic { max_types } => {
+                write!(f, "Dynamic")?;
+                if let Some(max_types) = max_types {
+                    write!(f, "(max_types={max_types})")?;
+                }
+                Ok(())
+            }
+            DT::Point => write!(f, "Point"),
+            DT::Ring => write!(f, "Ring"),
+            DT::LineString => write!(f, "LineString"),
+            DT::MultiLineString => write!(f, "MultiLineString"),
+            DT::Polygon => write!(f, "Polygon"),
+            DT::MultiPolygon => write!(f, "MultiPolygon"),
             DT::Nothing => write!(f, "Nothing"),
         }
     }
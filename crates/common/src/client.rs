@@ -1,6 +1,7 @@
 use crate::config::ConnectionConfig;
 use bytes::Bytes;
-use serde::de::DeserializeOwned;
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_with::{serde_as, DisplayFromStr};
 use std::error::Error;
 use tracing::Instrument;
 
@@ -52,6 +53,78 @@ pub async fn execute_bytes_query(
     Ok(response)
 }
 
+/// Query statistics, as returned by ClickHouse in the `X-ClickHouse-Summary` response header.
+/// Counts are serialized as strings. The written counts are required, other fields are not present in all server versions.
+/// Written counts include rows written to materialized views attached to the target table.
+#[serde_as]
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+pub struct QuerySummary {
+    #[serde_as(as = "DisplayFromStr")]
+    #[serde(default)]
+    pub read_rows: u64,
+    #[serde_as(as = "DisplayFromStr")]
+    #[serde(default)]
+    pub read_bytes: u64,
+    #[serde_as(as = "DisplayFromStr")]
+    pub written_rows: u64,
+    #[serde_as(as = "DisplayFromStr")]
+    pub written_bytes: u64,
+    #[serde_as(as = "DisplayFromStr")]
+    #[serde(default)]
+    pub result_rows: u64,
+    #[serde_as(as = "DisplayFromStr")]
+    #[serde(default)]
+    pub result_bytes: u64,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum MutationQueryError {
+    #[error(transparent)]
+    Request(#[from] reqwest::Error),
+    #[error("unable to parse X-ClickHouse-Summary response header {header}: {source}")]
+    InvalidSummary {
+        header: String,
+        source: serde_json::Error,
+    },
+}
+
+/// Execute a statement, returning the response body along with the query summary.
+/// The summary is `None` if the response has no `X-ClickHouse-Summary` header, and an error if the header could not be parsed.
+/// Pass `wait_end_of_query=1` as a parameter for the summary to reflect the complete query
+pub async fn execute_mutation_query(
+    client: &reqwest::Client,
+    connection_config: &ConnectionConfig,
+    statement: &str,
+    parameters: &Vec<(String, String)>,
+) -> Result<(Bytes, Option<QuerySummary>), MutationQueryError> {
+    let response = execute_query(client, connection_config, statement, parameters)
+        .await?
+        .error_for_status()?;
+
+    let summary = response
+        .headers()
+        .get("X-ClickHouse-Summary")
+        .map(|header| parse_query_summary(&String::from_utf8_lossy(header.as_bytes())))
+        .transpose()?;
+
+    let response = response
+        .bytes()
+        .instrument(tracing::info_span!(
+            "Read HTTP response",
+            internal.visibility = "user"
+        ))
+        .await?;
+
+    Ok((response, summary))
+}
+
+fn parse_query_summary(header: &str) -> Result<QuerySummary, MutationQueryError> {
+    serde_json::from_str(header).map_err(|source| MutationQueryError::InvalidSummary {
+        header: header.to_owned(),
+        source,
+    })
+}
+
 pub async fn execute_text_query<T: DeserializeOwned>(
     client: &reqwest::Client,
     connection_config: &ConnectionConfig,
@@ -107,3 +180,66 @@ pub async fn ping(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn can_parse_query_summary() {
+        // as sent by ClickHouse for an insert, with every count serialized as a string
+        let header = r#"{"read_rows":"0","read_bytes":"0","written_rows":"2","written_bytes":"64","total_rows_to_read":"0","result_rows":"2","result_bytes":"64","elapsed_ns":"1871203"}"#;
+
+        assert_eq!(
+            parse_query_summary(header).expect("Should parse summary header"),
+            QuerySummary {
+                read_rows: 0,
+                read_bytes: 0,
+                written_rows: 2,
+                written_bytes: 64,
+                result_rows: 2,
+                result_bytes: 64,
+            }
+        );
+    }
+
+    #[test]
+    fn can_parse_query_summary_without_result_counts() {
+        // older server versions do not report result counts
+        let header = r#"{"read_rows":"10","read_bytes":"80","written_rows":"10","written_bytes":"80","total_rows_to_read":"10"}"#;
+
+        assert_eq!(
+            parse_query_summary(header).expect("Should parse summary header"),
+            QuerySummary {
+                read_rows: 10,
+                read_bytes: 80,
+                written_rows: 10,
+                written_bytes: 80,
+                result_rows: 0,
+                result_bytes: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn invalid_query_summary_errors_with_header() {
+        let test_cases = vec![
+            // written counts are required
+            r#"{"read_rows":"0","read_bytes":"0"}"#,
+            // counts are strings, not numbers
+            r#"{"written_rows":2,"written_bytes":64}"#,
+            r#"{"written_rows":"two","written_bytes":"64"}"#,
+            "",
+        ];
+
+        for header in test_cases {
+            match parse_query_summary(header) {
+                Err(MutationQueryError::InvalidSummary {
+                    header: error_header,
+                    ..
+                }) => assert_eq!(error_header, header),
+                other => panic!("Expected an invalid summary error for {header}, got {other:?}"),
+            }
+        }
+    }
+}
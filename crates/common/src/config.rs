@@ -1,12 +1,18 @@
 use crate::{
     clickhouse_parser::{datatype::ClickHouseDataType, parameterized_query::ParameterizedQuery},
     config_file::{
-        MaybeClickhouseDataType, ParameterizedQueryConfigFile, ParameterizedQueryExposedAs,
-        PrimaryKey, ReturnType, ServerConfigFile, TableConfigFile, CONFIG_FILE_NAME,
+        AsyncInsert, MaybeClickhouseDataType, MutationsSync, ParameterizedAggregateFunction,
+        ParameterizedQueryConfigFile, ParameterizedQueryExposedAs, PrimaryKey, ReturnType,
+        ServerConfigFile, TableConfigFile, CONFIG_FILE_NAME,
     },
     format::display_period_separated,
+    schema::{
+        procedure::ClickHouseProcedure,
+        single_column_aggregate_function::ClickHouseSingleColumnAggregateFunction,
+        type_definition::POINT_DISTANCE_TYPE,
+    },
 };
-use ndc_models::{ArgumentName, CollectionName, FieldName, ObjectTypeName};
+use ndc_models::{AggregateFunctionName, ArgumentName, CollectionName, FieldName, ObjectTypeName};
 use std::{
     collections::{BTreeMap, HashMap},
     env, io,
@@ -24,6 +30,7 @@ pub struct ServerConfig {
     pub table_types: BTreeMap<ObjectTypeName, TableType>,
     pub tables: BTreeMap<CollectionName, TableConfig>,
     pub queries: BTreeMap<CollectionName, ParameterizedQueryConfig>,
+    pub aggregate_functions: BTreeMap<AggregateFunctionName, ParameterizedAggregateFunction>,
 }
 
 #[derive(Debug, Clone)]
@@ -49,8 +56,13 @@ pub struct TableConfig {
     pub comment: Option<String>,
     pub primary_key: Option<PrimaryKey>,
     pub arguments: BTreeMap<ArgumentName, ClickHouseDataType>,
+    /// Whether this is a view. Not set for tables introspected before views were marked
+    pub view: Option<bool>,
     // this key coresponds to a return type definition in the config table types
     pub return_type: ObjectTypeName,
+    pub destructive_procedures: bool,
+    pub mutations_sync: Option<MutationsSync>,
+    pub async_insert: Option<AsyncInsert>,
 }
 
 #[derive(Debug, Clone)]
@@ -208,7 +220,23 @@ pub async fn read_server_config(
             Ok(table_type)
         }))
         .filter_map(|table_type| table_type.transpose())
-        .collect::<Result<_, ConfigurationError>>()?;
+        .collect::<Result<BTreeMap<ObjectTypeName, TableType>, ConfigurationError>>()?;
+
+    // return types share a namespace with the object type generated for the argument of geo operators
+    if table_types.contains_key(POINT_DISTANCE_TYPE) {
+        let section = if config.tables.contains_key(POINT_DISTANCE_TYPE) {
+            "tables"
+        } else {
+            "queries"
+        };
+
+        return Err(ConfigurationError::ValidateError {
+            file_path: file_path.to_owned(),
+            node_path: vec![section.to_string(), POINT_DISTANCE_TYPE.to_string()],
+            message: "Name conflicts with the argument type of the _within_distance operator"
+                .to_string(),
+        });
+    }
 
     let tables = config
         .tables
@@ -221,6 +249,10 @@ pub async fn read_server_config(
                     schema: table_config.schema.to_owned(),
                     comment: table_config.comment.to_owned(),
                     primary_key: table_config.primary_key.to_owned(),
+                    view: table_config.view,
+                    destructive_procedures: table_config.destructive_procedures,
+                    mutations_sync: table_config.mutations_sync,
+                    async_insert: table_config.async_insert.to_owned(),
                     return_type: match &table_config.return_type {
                         ReturnType::Definition { .. } => table_alias.to_string().into(),
                         ReturnType::TableReference {
@@ -259,6 +291,28 @@ pub async fn read_server_config(
     let mut queries = BTreeMap::new();
 
     for (query_alias, query_config) in config.queries.clone() {
+        // queries exposed as procedures share a namespace with the procedures generated for tables
+        if !matches!(
+            query_config.exposed_as,
+            ParameterizedQueryExposedAs::Collection
+        ) {
+            let conflicting_table = tables.iter().find(|(table_alias, table)| {
+                ClickHouseProcedure::table_procedures(table_alias, table)
+                    .iter()
+                    .any(|procedure| procedure.name().as_str() == query_alias.as_str())
+            });
+
+            if let Some((table_alias, _)) = conflicting_table {
+                return Err(ConfigurationError::ValidateError {
+                    file_path: file_path.to_owned(),
+                    node_path: vec!["queries".to_string(), query_alias.to_string()],
+                    message: format!(
+                        "Name conflicts with a procedure generated for table {table_alias}"
+                    ),
+                });
+            }
+        }
+
         let query_file_path = configuration_dir.join(&query_config.file);
         let file_content =
             fs::read_to_string(&query_file_path)
@@ -296,6 +350,36 @@ pub async fn read_server_config(
         queries.insert(query_alias.to_owned(), query_definition);
     }
 
+    let aggregate_functions = config
+        .aggregate_functions
+        .iter()
+        .map(|(function_name, function)| {
+            let validate_error = |message: &str| ConfigurationError::ValidateError {
+                file_path: file_path.to_owned(),
+                node_path: vec!["aggregate_functions".to_string(), function_name.to_string()],
+                message: message.to_string(),
+            };
+
+            if ClickHouseSingleColumnAggregateFunction::from_str(function_name.inner()).is_ok() {
+                return Err(validate_error(
+                    "Name conflicts with a built in aggregate function",
+                ));
+            }
+
+            match function {
+                ParameterizedAggregateFunction::Quantile { level }
+                    if !(0.0..=1.0).contains(level) =>
+                {
+                    Err(validate_error("Quantile level must be between 0 and 1"))
+                }
+                ParameterizedAggregateFunction::TopK { k: 0 } => {
+                    Err(validate_error("TopK k must be greater than 0"))
+                }
+                _ => Ok((function_name.to_owned(), function.to_owned())),
+            }
+        })
+        .collect::<Result<_, ConfigurationError>>()?;
+
     let config = ServerConfig {
         connection,
         // hardcoding separator for now, to avoid prematurely exposing configuration options we may not want to keep
@@ -304,6 +388,7 @@ pub async fn read_server_config(
         table_types,
         tables,
         queries,
+        aggregate_functions,
     };
 
     Ok(config)
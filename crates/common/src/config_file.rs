@@ -1,5 +1,8 @@
-use crate::clickhouse_parser::datatype::ClickHouseDataType;
-use ndc_models::{ArgumentName, CollectionName, FieldName};
+use crate::{
+    clickhouse_parser::datatype::ClickHouseDataType,
+    schema::single_column_aggregate_function::ClickHouseParametricAggregateFunction,
+};
+use ndc_models::{AggregateFunctionName, ArgumentName, CollectionName, FieldName};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
@@ -21,6 +24,10 @@ pub struct ServerConfigFile {
     /// Note the names must not match table names
     #[serde(skip_serializing_if = "BTreeMap::is_empty", default)]
     pub queries: BTreeMap<CollectionName, ParameterizedQueryConfigFile>,
+    /// Optionally define variants of parametric aggregate functions here, such as `quantile(0.95)` or `topK(10)`
+    /// The map key is the name the aggregate function is exposed under, and must not match a built in aggregate function
+    #[serde(skip_serializing_if = "BTreeMap::is_empty", default)]
+    pub aggregate_functions: BTreeMap<AggregateFunctionName, ParameterizedAggregateFunction>,
 }
 
 impl Default for ServerConfigFile {
@@ -29,6 +36,7 @@ impl Default for ServerConfigFile {
             schema: CONFIG_SCHEMA_FILE_NAME.to_string(),
             tables: Default::default(),
             queries: Default::default(),
+            aggregate_functions: Default::default(),
         }
     }
 }
@@ -46,10 +54,94 @@ pub struct TableConfigFile {
     pub primary_key: Option<PrimaryKey>,
     #[serde(skip_serializing_if = "BTreeMap::is_empty", default)]
     pub arguments: BTreeMap<ArgumentName, String>,
+    /// Whether this is a view, sourced from the database table type.
+    /// Views cannot be written to, so no procedures are generated for them.
+    /// If not set, the table is treated as read-only until the configuration is updated.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub view: Option<bool>,
     /// The map key is a column alias identifying the table and may be customized.
     /// It defaults to the table name.
     /// When the configuration is updated, the column is identified by name, and changes to the alias are preserved.
     pub return_type: ReturnType,
+    /// Generate delete and update procedures for this table, which may modify or remove any of its rows.
+    /// If not set, only insert procedures are generated.
+    #[serde(skip_serializing_if = "std::ops::Not::not", default)]
+    pub destructive_procedures: bool,
+    /// Whether update procedures for this table wait for the mutation to complete.
+    /// If not set, the server default is used, which returns without waiting.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mutations_sync: Option<MutationsSync>,
+    /// Use asynchronous inserts for insert procedures on this table.
+    /// If not set, inserts are written to the table before the procedure returns.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub async_insert: Option<AsyncInsert>,
+}
+
+/// Maps to the ClickHouse `mutations_sync` setting
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum MutationsSync {
+    /// Return as soon as the mutation is scheduled. The mutation completes in the background.
+    Async,
+    /// Wait for the mutation to complete on the current server
+    Local,
+    /// Wait for the mutation to complete on all replicas
+    AllReplicas,
+}
+
+impl MutationsSync {
+    /// the value for the `mutations_sync` setting
+    pub fn setting_value(&self) -> &'static str {
+        match self {
+            MutationsSync::Async => "0",
+            MutationsSync::Local => "1",
+            MutationsSync::AllReplicas => "2",
+        }
+    }
+}
+
+/// Buffer inserted rows on the server, flushing them to the table in batches.
+/// Suited to many small inserts
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct AsyncInsert {
+    /// Whether insert procedures wait for the rows to be flushed to the table.
+    /// If false, procedures return as soon as the rows are buffered.
+    pub wait_for_async_insert: bool,
+    /// The maximum time in milliseconds rows are buffered before being flushed.
+    /// If not set, the server default is used.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub busy_timeout_ms: Option<u64>,
+}
+
+/// A ClickHouse parametric aggregate function, with its parameters fixed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "function", rename_all = "snake_case")]
+pub enum ParameterizedAggregateFunction {
+    /// `quantile(level)`, available for numeric types
+    Quantile {
+        /// The level of the quantile, between 0 and 1
+        level: f64,
+    },
+    /// `topK(k)`, the most frequent values, available for numeric, string, UUID, IP address and enum types
+    TopK {
+        /// The maximum number of values returned
+        k: u32,
+    },
+}
+
+impl ParameterizedAggregateFunction {
+    /// The parametric aggregate function this is a variant of.
+    /// Which types a variant is available for, and its result type, are defined in the type catalogue
+    pub fn parametric_function(&self) -> ClickHouseParametricAggregateFunction {
+        match self {
+            ParameterizedAggregateFunction::Quantile { .. } => {
+                ClickHouseParametricAggregateFunction::Quantile
+            }
+            ParameterizedAggregateFunction::TopK { .. } => {
+                ClickHouseParametricAggregateFunction::TopK
+            }
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
@@ -117,7 +209,8 @@ impl Default for ReturnType {
 
 #[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
 pub struct ParameterizedQueryConfigFile {
-    /// Whether this query should be exposed as a procedure (mutating) or collection (non-mutating)
+    /// Whether this query should be exposed as a procedure (mutating) or collection (non-mutating).
+    /// Use `insert_procedure` for `INSERT INTO ... SELECT` queries, exposed as a procedure returning the number of rows and bytes written.
     pub exposed_as: ParameterizedQueryExposedAs,
     /// A comment that will be exposed in the schema
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -136,6 +229,7 @@ pub enum ParameterizedQueryExposedAs {
     #[default]
     Collection,
     Procedure,
+    InsertProcedure,
 }
 
 pub const CONFIG_FILE_NAME: &str = "configuration.json";
@@ -3,13 +3,15 @@ use crate::{
         datatype::ClickHouseDataType,
         parameterized_query::{Parameter, ParameterType, ParameterizedQueryElement},
     },
-    config::ServerConfig,
+    config::{ParameterizedQueryConfig, ServerConfig},
     config_file::{ParameterizedQueryExposedAs, PrimaryKey},
 };
-use ndc_models::{self as models, ObjectTypeName, ScalarTypeName};
+use ndc_models::{self as models, ArgumentName, CollectionName, ObjectTypeName, ScalarTypeName};
+use procedure::ClickHouseProcedure;
 use std::collections::BTreeMap;
 use type_definition::{ClickHouseTypeDefinition, SchemaTypeDefinitions};
 pub mod binary_comparison_operator;
+pub mod procedure;
 pub mod single_column_aggregate_function;
 pub mod type_definition;
 
@@ -29,7 +31,8 @@ pub fn schema_response(configuration: &ServerConfig) -> models::SchemaResponse {
                 &configuration.namespace_separator,
             );
 
-            let SchemaTypeDefinitions { scalars, objects } = type_definition.type_definitions();
+            let SchemaTypeDefinitions { scalars, objects } =
+                type_definition.type_definitions(&configuration.aggregate_functions);
 
             for (name, definition) in objects {
                 object_type_definitions.insert(name, definition);
@@ -46,7 +49,7 @@ pub fn schema_response(configuration: &ServerConfig) -> models::SchemaResponse {
                 models::ObjectField {
                     description: None,
                     r#type: type_definition.type_identifier(),
-                    arguments: BTreeMap::new(),
+                    arguments: type_definition.field_arguments(),
                 },
             ));
         }
@@ -68,7 +71,8 @@ pub fn schema_response(configuration: &ServerConfig) -> models::SchemaResponse {
                 table_alias.inner(),
                 &configuration.namespace_separator,
             );
-            let SchemaTypeDefinitions { scalars, objects } = type_definition.type_definitions();
+            let SchemaTypeDefinitions { scalars, objects } =
+                type_definition.type_definitions(&configuration.aggregate_functions);
 
             for (name, definition) in objects {
                 object_type_definitions.insert(name, definition);
@@ -96,7 +100,8 @@ pub fn schema_response(configuration: &ServerConfig) -> models::SchemaResponse {
                     &configuration.namespace_separator,
                 );
 
-                let SchemaTypeDefinitions { scalars, objects } = type_definition.type_definitions();
+                let SchemaTypeDefinitions { scalars, objects } =
+                    type_definition.type_definitions(&configuration.aggregate_functions);
 
                 for (name, definition) in objects {
                     object_type_definitions.insert(name, definition);
@@ -111,6 +116,19 @@ pub fn schema_response(configuration: &ServerConfig) -> models::SchemaResponse {
         }
     }
 
+    let procedures = ClickHouseProcedure::all(configuration);
+
+    for procedure in &procedures {
+        let SchemaTypeDefinitions { scalars, objects } = procedure.type_definitions(configuration);
+
+        for (name, definition) in objects {
+            object_type_definitions.insert(name, definition);
+        }
+        for (name, definition) in scalars {
+            scalar_type_definitions.insert(name, definition);
+        }
+    }
+
     let table_collections = configuration
         .tables
         .iter()
@@ -158,36 +176,7 @@ pub fn schema_response(configuration: &ServerConfig) -> models::SchemaResponse {
             query_config.exposed_as == ParameterizedQueryExposedAs::Collection
         })
         .map(|(query_alias, query_config)| {
-            // arguments with the same name may apear in multiple places in the same query
-            // collecting into a map effectively de-duplicates the arguments
-            let arguments = query_config
-                .query
-                .elements
-                .iter()
-                .filter_map(|element| match element {
-                    ParameterizedQueryElement::String(_) => None,
-                    ParameterizedQueryElement::Parameter(Parameter { name, r#type }) => {
-                        let data_type = match r#type {
-                            ParameterType::Identifier => &ClickHouseDataType::String,
-                            ParameterType::DataType(t) => t,
-                        };
-                        let type_definition = ClickHouseTypeDefinition::from_query_argument(
-                            data_type,
-                            name.value(),
-                            query_alias.inner(),
-                            &configuration.namespace_separator,
-                        );
-
-                        Some((
-                            name.value().to_owned().into(),
-                            models::ArgumentInfo {
-                                description: None,
-                                argument_type: type_definition.type_identifier(),
-                            },
-                        ))
-                    }
-                })
-                .collect();
+            let arguments = query_arguments(query_alias, query_config, configuration);
 
             models::CollectionInfo {
                 name: query_alias.to_owned(),
@@ -208,6 +197,47 @@ pub fn schema_response(configuration: &ServerConfig) -> models::SchemaResponse {
         object_types: object_type_definitions,
         collections,
         functions: vec![],
-        procedures: vec![],
+        procedures: procedures
+            .iter()
+            .map(|procedure| procedure.procedure_info(configuration))
+            .collect(),
     }
 }
+
+/// the arguments for a parameterized query
+fn query_arguments(
+    query_alias: &CollectionName,
+    query_config: &ParameterizedQueryConfig,
+    configuration: &ServerConfig,
+) -> BTreeMap<ArgumentName, models::ArgumentInfo> {
+    // arguments with the same name may apear in multiple places in the same query
+    // collecting into a map effectively de-duplicates the arguments
+    query_config
+        .query
+        .elements
+        .iter()
+        .filter_map(|element| match element {
+            ParameterizedQueryElement::String(_) => None,
+            ParameterizedQueryElement::Parameter(Parameter { name, r#type }) => {
+                let data_type = match r#type {
+                    ParameterType::Identifier => &ClickHouseDataType::String,
+                    ParameterType::DataType(t) => t,
+                };
+                let type_definition = ClickHouseTypeDefinition::from_query_argument(
+                    data_type,
+                    name.value(),
+                    query_alias.inner(),
+                    &configuration.namespace_separator,
+                );
+
+                Some((
+                    name.value().to_owned().into(),
+                    models::ArgumentInfo {
+                        description: None,
+                        argument_type: type_definition.type_identifier(),
+                    },
+                ))
+            }
+        })
+        .collect()
+}
@@ -28,4 +28,24 @@ pub enum ClickHouseBinaryComparisonOperator {
     NotIn,
     #[strum(to_string = "_match")]
     Match,
+    #[strum(to_string = "_contains")]
+    Contains,
+    #[strum(to_string = "_icontains")]
+    IContains,
+    #[strum(to_string = "_starts_with")]
+    StartsWith,
+    #[strum(to_string = "_ends_with")]
+    EndsWith,
+    #[strum(to_string = "_has_token")]
+    HasToken,
+    #[strum(to_string = "_has_key")]
+    HasKey,
+    #[strum(to_string = "_has_any_key")]
+    HasAnyKey,
+    #[strum(to_string = "_has_all_keys")]
+    HasAllKeys,
+    #[strum(to_string = "_within_polygon")]
+    WithinPolygon,
+    #[strum(to_string = "_within_distance")]
+    WithinDistance,
 }
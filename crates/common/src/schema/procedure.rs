@@ -0,0 +1,486 @@
+use super::type_definition::{ClickHouseTypeDefinition, SchemaTypeDefinitions};
+use crate::{
+    clickhouse_parser::datatype::ClickHouseDataType,
+    config::{ParameterizedQueryConfig, ServerConfig, TableConfig},
+    config_file::ParameterizedQueryExposedAs,
+};
+use ndc_models::{self as models, CollectionName, FieldName, ObjectTypeName, ProcedureName};
+use std::collections::BTreeMap;
+
+/// name of the argument taking the rows to insert
+pub const INSERT_ROWS_ARGUMENT: &str = "rows";
+/// name of the argument taking the predicate selecting rows to delete or update
+pub const PREDICATE_ARGUMENT: &str = "where";
+/// name of the argument taking the columns to update, and their new values
+pub const UPDATE_SET_ARGUMENT: &str = "set";
+/// name of the response field holding the number of rows affected by a mutation
+pub const AFFECTED_ROWS_FIELD: &str = "affected_rows";
+/// name of the response field holding the rows submitted for insertion.
+/// ClickHouse has no `RETURNING` clause, so these are the input rows rather than rows read back from the table
+pub const SUBMITTED_ROWS_FIELD: &str = "submitted_rows";
+/// name of the response field indicating whether inserted rows were written to the table, or only buffered
+pub const FLUSHED_FIELD: &str = "flushed";
+/// name of the response field holding the number of rows written by an `INSERT INTO ... SELECT` query
+pub const WRITTEN_ROWS_FIELD: &str = "written_rows";
+/// name of the response field holding the number of bytes written by an `INSERT INTO ... SELECT` query
+pub const WRITTEN_BYTES_FIELD: &str = "written_bytes";
+/// name of the response field indicating whether a delete or update had finished when the procedure returned
+pub const COMPLETED_FIELD: &str = "completed";
+
+const INSERT_PREFIX: &str = "insert_";
+const DELETE_PREFIX: &str = "delete_";
+const UPDATE_PREFIX: &str = "update_";
+const WHERE_SUFFIX: &str = "_where";
+
+/// Procedures generated by the connector from the tables and queries in configuration
+#[derive(Debug, Clone, Copy)]
+pub enum ClickHouseProcedure<'a> {
+    /// `insert_<table>`: insert an array of rows into a table
+    Insert {
+        table_alias: &'a CollectionName,
+        table: &'a TableConfig,
+    },
+    /// `delete_<table>_where`: delete rows matching a predicate from a table
+    DeleteWhere {
+        table_alias: &'a CollectionName,
+        table: &'a TableConfig,
+    },
+    /// `update_<table>_where`: update columns of rows matching a predicate
+    UpdateWhere {
+        table_alias: &'a CollectionName,
+        table: &'a TableConfig,
+    },
+    /// a parameterized query with `exposed_as` set to `procedure`. Exposed under the query alias
+    NativeQuery {
+        query_alias: &'a CollectionName,
+        query: &'a ParameterizedQueryConfig,
+    },
+    /// a parameterized `INSERT INTO ... SELECT` query with `exposed_as` set to `insert_procedure`.
+    /// Exposed under the query alias
+    InsertSelect {
+        query_alias: &'a CollectionName,
+        query: &'a ParameterizedQueryConfig,
+    },
+}
+
+impl<'a> ClickHouseProcedure<'a> {
+    /// all procedures exposed for this configuration
+    pub fn all(configuration: &'a ServerConfig) -> Vec<Self> {
+        let table_procedures = configuration
+            .tables
+            .iter()
+            .flat_map(|(table_alias, table)| Self::table_procedures(table_alias, table));
+
+        let native_queries = configuration
+            .queries
+            .iter()
+            .filter_map(|(query_alias, query)| Self::query_procedure(query_alias, query));
+
+        table_procedures.chain(native_queries).collect()
+    }
+    /// the insert, delete, and update procedures generated for a table
+    pub fn table_procedures(table_alias: &'a CollectionName, table: &'a TableConfig) -> Vec<Self> {
+        if !is_writable(table) {
+            return vec![];
+        }
+
+        if !table.destructive_procedures {
+            return vec![Self::Insert { table_alias, table }];
+        }
+
+        vec![
+            Self::Insert { table_alias, table },
+            Self::DeleteWhere { table_alias, table },
+            Self::UpdateWhere { table_alias, table },
+        ]
+    }
+    /// the procedure a parameterized query is exposed as, if any
+    fn query_procedure(
+        query_alias: &'a CollectionName,
+        query: &'a ParameterizedQueryConfig,
+    ) -> Option<Self> {
+        match query.exposed_as {
+            ParameterizedQueryExposedAs::Collection => None,
+            ParameterizedQueryExposedAs::Procedure => {
+                Some(Self::NativeQuery { query_alias, query })
+            }
+            ParameterizedQueryExposedAs::InsertProcedure => {
+                Some(Self::InsertSelect { query_alias, query })
+            }
+        }
+    }
+    /// look up a procedure by the name it is exposed as.
+    /// Aliases of queries exposed as procedures are checked for conflicts with generated procedure names
+    /// when reading configuration, but queries exposed as collections may share a name with a generated procedure
+    pub fn from_name(name: &ProcedureName, configuration: &'a ServerConfig) -> Option<Self> {
+        let query_procedure = configuration
+            .queries
+            .get_key_value(name.as_str())
+            .and_then(|(query_alias, query)| Self::query_procedure(query_alias, query));
+
+        if query_procedure.is_some() {
+            return query_procedure;
+        }
+
+        let table = |table_alias: &str| {
+            configuration
+                .tables
+                .get_key_value(table_alias)
+                .filter(|(_, table)| is_writable(table))
+        };
+
+        let name = name.as_str();
+
+        if let Some((table_alias, table)) = name.strip_prefix(INSERT_PREFIX).and_then(table) {
+            return Some(Self::Insert { table_alias, table });
+        }
+
+        let table_alias = |prefix: &str| {
+            name.strip_prefix(prefix)
+                .and_then(|name| name.strip_suffix(WHERE_SUFFIX))
+                .and_then(table)
+                .filter(|(_, table)| table.destructive_procedures)
+        };
+
+        if let Some((table_alias, table)) = table_alias(DELETE_PREFIX) {
+            return Some(Self::DeleteWhere { table_alias, table });
+        }
+
+        if let Some((table_alias, table)) = table_alias(UPDATE_PREFIX) {
+            return Some(Self::UpdateWhere { table_alias, table });
+        }
+
+        None
+    }
+    pub fn name(&self) -> ProcedureName {
+        match self {
+            ClickHouseProcedure::Insert { table_alias, .. } => {
+                format!("{INSERT_PREFIX}{table_alias}").into()
+            }
+            ClickHouseProcedure::DeleteWhere { table_alias, .. } => {
+                format!("{DELETE_PREFIX}{table_alias}{WHERE_SUFFIX}").into()
+            }
+            ClickHouseProcedure::UpdateWhere { table_alias, .. } => {
+                format!("{UPDATE_PREFIX}{table_alias}{WHERE_SUFFIX}").into()
+            }
+            ClickHouseProcedure::NativeQuery { query_alias, .. }
+            | ClickHouseProcedure::InsertSelect { query_alias, .. } => {
+                query_alias.inner().to_owned().into()
+            }
+        }
+    }
+    /// the name of the object type returned by this procedure
+    pub fn result_type_name(&self) -> ObjectTypeName {
+        format!("{}_response", self.name()).into()
+    }
+    /// the name of the object type taking a row for an insert procedure
+    pub fn row_type_name(&self) -> ObjectTypeName {
+        format!("{}_row", self.name()).into()
+    }
+    /// the name of the object type taking the new column values for an update procedure
+    pub fn set_type_name(&self) -> ObjectTypeName {
+        format!("{}_set", self.name()).into()
+    }
+    pub fn procedure_info(&self, configuration: &ServerConfig) -> models::ProcedureInfo {
+        match self {
+            ClickHouseProcedure::Insert { table_alias, .. } => models::ProcedureInfo {
+                name: self.name(),
+                description: Some(format!("Insert rows into {table_alias}")),
+                arguments: BTreeMap::from([(
+                    INSERT_ROWS_ARGUMENT.into(),
+                    models::ArgumentInfo {
+                        description: Some(
+                            "The rows to insert. Omitted or null columns take their default value"
+                                .to_string(),
+                        ),
+                        argument_type: models::Type::Array {
+                            element_type: Box::new(models::Type::Named {
+                                name: self.row_type_name().into_inner(),
+                            }),
+                        },
+                    },
+                )]),
+                result_type: models::Type::Named {
+                    name: self.result_type_name().into_inner(),
+                },
+            },
+            ClickHouseProcedure::DeleteWhere { table_alias, table } => models::ProcedureInfo {
+                name: self.name(),
+                description: Some(format!(
+                    "Delete rows matching a predicate from {table_alias}. ClickHouse does not report the number of rows deleted"
+                )),
+                arguments: BTreeMap::from([(
+                    PREDICATE_ARGUMENT.into(),
+                    models::ArgumentInfo {
+                        description: Some("The rows to delete".to_string()),
+                        argument_type: models::Type::Predicate {
+                            object_type_name: table.return_type.to_owned(),
+                        },
+                    },
+                )]),
+                result_type: models::Type::Named {
+                    name: self.result_type_name().into_inner(),
+                },
+            },
+            ClickHouseProcedure::UpdateWhere { table_alias, table } => models::ProcedureInfo {
+                name: self.name(),
+                description: Some(format!(
+                    "Update columns of rows matching a predicate in {table_alias}"
+                )),
+                arguments: BTreeMap::from([
+                    (
+                        PREDICATE_ARGUMENT.into(),
+                        models::ArgumentInfo {
+                            description: Some("The rows to update".to_string()),
+                            argument_type: models::Type::Predicate {
+                                object_type_name: table.return_type.to_owned(),
+                            },
+                        },
+                    ),
+                    (
+                        UPDATE_SET_ARGUMENT.into(),
+                        models::ArgumentInfo {
+                            description: Some(
+                                "The columns to update, and their new values".to_string(),
+                            ),
+                            argument_type: models::Type::Named {
+                                name: self.set_type_name().into_inner(),
+                            },
+                        },
+                    ),
+                ]),
+                result_type: models::Type::Named {
+                    name: self.result_type_name().into_inner(),
+                },
+            },
+            ClickHouseProcedure::NativeQuery { query_alias, query } => models::ProcedureInfo {
+                name: self.name(),
+                description: query.comment.to_owned(),
+                arguments: super::query_arguments(query_alias, query, configuration),
+                result_type: models::Type::Array {
+                    element_type: Box::new(models::Type::Named {
+                        name: query.return_type.to_owned().into_inner(),
+                    }),
+                },
+            },
+            ClickHouseProcedure::InsertSelect { query_alias, query } => models::ProcedureInfo {
+                name: self.name(),
+                description: query.comment.to_owned(),
+                arguments: super::query_arguments(query_alias, query, configuration),
+                result_type: models::Type::Named {
+                    name: self.result_type_name().into_inner(),
+                },
+            },
+        }
+    }
+    /// object and scalar types required by this procedure, in addition to the table types
+    pub fn type_definitions(&self, configuration: &ServerConfig) -> SchemaTypeDefinitions {
+        if let ClickHouseProcedure::NativeQuery { .. } = self {
+            // native queries return rows of their return type, which is defined with the table types
+            return SchemaTypeDefinitions {
+                scalars: vec![],
+                objects: vec![],
+            };
+        }
+
+        if let ClickHouseProcedure::InsertSelect { .. } = self {
+            let written_rows = ClickHouseTypeDefinition::from_query_return_type(
+                &ClickHouseDataType::UInt64,
+                WRITTEN_ROWS_FIELD,
+                self.name().inner(),
+                &configuration.namespace_separator,
+            );
+            let written_bytes = ClickHouseTypeDefinition::from_query_return_type(
+                &ClickHouseDataType::UInt64,
+                WRITTEN_BYTES_FIELD,
+                self.name().inner(),
+                &configuration.namespace_separator,
+            );
+
+            let SchemaTypeDefinitions { scalars, .. } =
+                written_rows.type_definitions(&configuration.aggregate_functions);
+
+            return SchemaTypeDefinitions {
+                scalars,
+                objects: vec![(
+                    self.result_type_name(),
+                    models::ObjectType {
+                        description: None,
+                        fields: BTreeMap::from([
+                            (
+                                WRITTEN_ROWS_FIELD.into(),
+                                models::ObjectField {
+                                    description: Some(
+                                        "The number of rows written, \
+                                        including rows written to materialized views attached to the target table"
+                                            .to_string(),
+                                    ),
+                                    r#type: written_rows.type_identifier(),
+                                    arguments: BTreeMap::new(),
+                                },
+                            ),
+                            (
+                                WRITTEN_BYTES_FIELD.into(),
+                                models::ObjectField {
+                                    description: Some("The number of bytes written".to_string()),
+                                    r#type: written_bytes.type_identifier(),
+                                    arguments: BTreeMap::new(),
+                                },
+                            ),
+                        ]),
+                    },
+                )],
+            };
+        }
+
+        // scalar fields of the result type, with their description
+        let mut scalars = vec![];
+        let mut scalar_field =
+            |field_name: &str, data_type: &ClickHouseDataType, description: &str| {
+                let type_definition = ClickHouseTypeDefinition::from_query_return_type(
+                    data_type,
+                    field_name,
+                    self.name().inner(),
+                    &configuration.namespace_separator,
+                );
+
+                scalars.extend(
+                    type_definition
+                        .type_definitions(&configuration.aggregate_functions)
+                        .scalars,
+                );
+
+                (
+                    FieldName::from(field_name),
+                    models::ObjectField {
+                        description: Some(description.to_string()),
+                        r#type: type_definition.type_identifier(),
+                        arguments: BTreeMap::new(),
+                    },
+                )
+            };
+
+        let mut fields = BTreeMap::new();
+
+        match self {
+            ClickHouseProcedure::Insert { .. } => {
+                // the summary counts rows written to attached materialized views, and nothing until buffered rows are flushed
+                fields.extend([
+                    scalar_field(
+                        AFFECTED_ROWS_FIELD,
+                        &ClickHouseDataType::UInt64,
+                        "The number of rows written, including rows written to materialized views attached to the table. Zero if the rows were only buffered",
+                    ),
+                    scalar_field(
+                        FLUSHED_FIELD,
+                        &ClickHouseDataType::Bool,
+                        "Whether the rows were written to the table, or only buffered",
+                    ),
+                ]);
+                fields.insert(
+                    SUBMITTED_ROWS_FIELD.into(),
+                    models::ObjectField {
+                        description: Some(
+                            "The rows as submitted. These are not read back from the table, so omitted columns are null rather than their default values".to_string(),
+                        ),
+                        r#type: models::Type::Array {
+                            element_type: Box::new(models::Type::Named {
+                                name: self.row_type_name().into_inner(),
+                            }),
+                        },
+                        arguments: BTreeMap::new(),
+                    },
+                );
+            }
+            // ClickHouse does not report the rows changed by lightweight deletes or update mutations
+            ClickHouseProcedure::DeleteWhere { .. } => fields.extend([scalar_field(
+                COMPLETED_FIELD,
+                &ClickHouseDataType::Bool,
+                "Always true: lightweight deletes return once the matching rows are marked as deleted",
+            )]),
+            ClickHouseProcedure::UpdateWhere { .. } => fields.extend([scalar_field(
+                COMPLETED_FIELD,
+                &ClickHouseDataType::Bool,
+                "Whether the mutation is known to have completed when the procedure returned. False if the table's mutations_sync option is not set or is async, in which case the mutation may still be running",
+            )]),
+            ClickHouseProcedure::NativeQuery { .. } | ClickHouseProcedure::InsertSelect { .. } => {}
+        }
+
+        let mut objects = vec![(
+            self.result_type_name(),
+            models::ObjectType {
+                description: None,
+                fields,
+            },
+        )];
+
+        match self {
+            // every column is optional, omitted columns take their default value
+            ClickHouseProcedure::Insert { table, .. } => objects.push((
+                self.row_type_name(),
+                models::ObjectType {
+                    description: None,
+                    fields: optional_column_fields(table, configuration),
+                },
+            )),
+            // every column is optional, only the columns present are updated
+            ClickHouseProcedure::UpdateWhere { table, .. } => objects.push((
+                self.set_type_name(),
+                models::ObjectType {
+                    description: None,
+                    fields: optional_column_fields(table, configuration),
+                },
+            )),
+            ClickHouseProcedure::DeleteWhere { .. }
+            | ClickHouseProcedure::NativeQuery { .. }
+            | ClickHouseProcedure::InsertSelect { .. } => {}
+        }
+
+        SchemaTypeDefinitions { scalars, objects }
+    }
+}
+
+/// Whether procedures may be generated for a table.
+/// Views, including parameterized views, cannot be written to.
+/// Tables introspected before views were marked are treated as read-only until the configuration is updated
+fn is_writable(table: &TableConfig) -> bool {
+    table.view == Some(false) && table.arguments.is_empty()
+}
+
+/// A field for each column of a table, typed as nullable so it may be omitted
+fn optional_column_fields(
+    table: &TableConfig,
+    configuration: &ServerConfig,
+) -> BTreeMap<FieldName, models::ObjectField> {
+    let Some(table_type) = configuration.table_types.get(&table.return_type) else {
+        return BTreeMap::new();
+    };
+
+    table_type
+        .columns
+        .iter()
+        .map(|(column_alias, column_type)| {
+            let type_definition = ClickHouseTypeDefinition::from_table_column(
+                column_type,
+                column_alias,
+                &table.return_type,
+                &configuration.namespace_separator,
+            );
+            let r#type = match type_definition.type_identifier() {
+                nullable @ models::Type::Nullable { .. } => nullable,
+                underlying_type => models::Type::Nullable {
+                    underlying_type: Box::new(underlying_type),
+                },
+            };
+
+            (
+                column_alias.to_owned(),
+                models::ObjectField {
+                    description: None,
+                    r#type,
+                    arguments: BTreeMap::new(),
+                },
+            )
+        })
+        .collect()
+}
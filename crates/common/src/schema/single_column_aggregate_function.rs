@@ -11,4 +11,30 @@ pub enum ClickHouseSingleColumnAggregateFunction {
     StddevSamp,
     VarPop,
     VarSamp,
+    Median,
+    #[strum(serialize = "quantile_50")]
+    Quantile50,
+    #[strum(serialize = "quantile_90")]
+    Quantile90,
+    #[strum(serialize = "quantile_95")]
+    Quantile95,
+    #[strum(serialize = "quantile_99")]
+    Quantile99,
+    Uniq,
+    UniqExact,
+    Any,
+    AnyLast,
+    GroupArray,
+    GroupUniqArray,
+    TopK,
+    BoolAnd,
+    BoolOr,
+}
+
+/// ClickHouse aggregate functions that take parameters, such as `quantile(level)`.
+/// These are not exposed directly, but through variants with fixed parameters defined in configuration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClickHouseParametricAggregateFunction {
+    Quantile,
+    TopK,
 }
@@ -1,11 +1,18 @@
-use crate::clickhouse_parser::datatype::{ClickHouseDataType, Identifier, SingleQuotedString};
+use crate::{
+    clickhouse_parser::datatype::{ClickHouseDataType, Identifier, SingleQuotedString},
+    config_file::ParameterizedAggregateFunction,
+};
 use indexmap::IndexMap;
-use ndc_models::{self as models, FieldName, ObjectTypeName, ScalarTypeName};
+use ndc_models::{
+    self as models, AggregateFunctionName, ArgumentName, FieldName, ObjectTypeName, ScalarTypeName,
+};
 use std::{collections::BTreeMap, iter};
 
 use super::{
     binary_comparison_operator::ClickHouseBinaryComparisonOperator,
-    single_column_aggregate_function::ClickHouseSingleColumnAggregateFunction,
+    single_column_aggregate_function::{
+        ClickHouseParametricAggregateFunction, ClickHouseSingleColumnAggregateFunction,
+    },
 };
 
 #[derive(Debug, Clone)]
@@ -34,6 +41,27 @@ impl<'a> NameSpace<'a> {
     }
 }
 
+/// An aggregate function in the catalogue of a scalar type
+#[derive(Debug, Clone, PartialEq)]
+enum CatalogueAggregateFunction {
+    /// exposed under its own name
+    BuiltIn(ClickHouseSingleColumnAggregateFunction),
+    /// exposed under the names of the variants defined in configuration, if any
+    Parametric(ClickHouseParametricAggregateFunction),
+}
+
+impl From<ClickHouseSingleColumnAggregateFunction> for CatalogueAggregateFunction {
+    fn from(function: ClickHouseSingleColumnAggregateFunction) -> Self {
+        Self::BuiltIn(function)
+    }
+}
+
+impl From<ClickHouseParametricAggregateFunction> for CatalogueAggregateFunction {
+    fn from(function: ClickHouseParametricAggregateFunction) -> Self {
+        Self::Parametric(function)
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct ClickHouseScalar(ClickHouseDataType);
 
@@ -45,19 +73,20 @@ impl ClickHouseScalar {
         // todo: recusively map large number types to string here
         self.0.clone()
     }
-    fn type_definition(&self) -> models::ScalarType {
+    fn type_definition(
+        &self,
+        parameterized_functions: &BTreeMap<AggregateFunctionName, ParameterizedAggregateFunction>,
+    ) -> models::ScalarType {
         models::ScalarType {
             representation: self.json_representation(),
             aggregate_functions: self
-                .aggregate_functions()
+                .exposed_aggregate_functions(parameterized_functions)
                 .into_iter()
-                .map(|(function, result_type)| {
+                .map(|(function_name, result_type)| {
                     (
-                        function.to_string().into(),
+                        function_name,
                         models::AggregateFunctionDefinition {
-                            result_type: models::Type::Named {
-                                name: result_type.to_string().into(),
-                            },
+                            result_type: aggregate_result_type(&result_type),
                         },
                     )
                 })
@@ -91,13 +120,57 @@ impl ClickHouseScalar {
                         | ClickHouseBinaryComparisonOperator::NotLike
                         | ClickHouseBinaryComparisonOperator::ILike
                         | ClickHouseBinaryComparisonOperator::NotILike
-                        | ClickHouseBinaryComparisonOperator::Match => {
+                        | ClickHouseBinaryComparisonOperator::Match
+                        | ClickHouseBinaryComparisonOperator::Contains
+                        | ClickHouseBinaryComparisonOperator::IContains
+                        | ClickHouseBinaryComparisonOperator::StartsWith
+                        | ClickHouseBinaryComparisonOperator::EndsWith
+                        | ClickHouseBinaryComparisonOperator::HasToken => {
                             models::ComparisonOperatorDefinition::Custom {
                                 argument_type: models::Type::Named {
                                     name: self.type_name().into_inner(),
                                 },
                             }
                         }
+                        ClickHouseBinaryComparisonOperator::HasKey => {
+                            models::ComparisonOperatorDefinition::Custom {
+                                argument_type: models::Type::Named {
+                                    name: self
+                                        .map_key_scalar()
+                                        .unwrap_or(self.to_owned())
+                                        .type_name()
+                                        .into_inner(),
+                                },
+                            }
+                        }
+                        ClickHouseBinaryComparisonOperator::HasAnyKey
+                        | ClickHouseBinaryComparisonOperator::HasAllKeys => {
+                            models::ComparisonOperatorDefinition::Custom {
+                                argument_type: models::Type::Array {
+                                    element_type: Box::new(models::Type::Named {
+                                        name: self
+                                            .map_key_scalar()
+                                            .unwrap_or(self.to_owned())
+                                            .type_name()
+                                            .into_inner(),
+                                    }),
+                                },
+                            }
+                        }
+                        ClickHouseBinaryComparisonOperator::WithinPolygon => {
+                            models::ComparisonOperatorDefinition::Custom {
+                                argument_type: models::Type::Named {
+                                    name: ClickHouseDataType::Polygon.to_string().into(),
+                                },
+                            }
+                        }
+                        ClickHouseBinaryComparisonOperator::WithinDistance => {
+                            models::ComparisonOperatorDefinition::Custom {
+                                argument_type: models::Type::Named {
+                                    name: POINT_DISTANCE_TYPE.into(),
+                                },
+                            }
+                        }
                     };
                     (operator.to_string().into(), definition)
                 })
@@ -143,17 +216,132 @@ impl ClickHouseScalar {
 
                 Some(Rep::Enum { one_of: variants })
             }
+            // maps are serialized as JSON objects
+            ClickHouseDataType::Map { .. } => Some(Rep::JSON),
+            // semi-structured types may hold any json value
+            ClickHouseDataType::Json(_)
+            | ClickHouseDataType::Variant(_)
+            | ClickHouseDataType::Dynamic { .. } => Some(Rep::JSON),
+            // geo types are serialized as GeoJSON-like coordinates, such as [x, y] for points
+            ClickHouseDataType::Point
+            | ClickHouseDataType::Ring
+            | ClickHouseDataType::LineString
+            | ClickHouseDataType::MultiLineString
+            | ClickHouseDataType::Polygon
+            | ClickHouseDataType::MultiPolygon => Some(Rep::JSON),
             _ => None,
         }
     }
-    fn aggregate_functions(
+    /// The scalar type of the keys of a map, used for map operators and key lookups
+    fn map_key_scalar(&self) -> Option<ClickHouseScalar> {
+        match &self.0 {
+            ClickHouseDataType::Map { key, value: _ } => {
+                match ClickHouseTypeDefinition::new(key, &NameSpace::new(vec![], "")) {
+                    ClickHouseTypeDefinition::Scalar(scalar) => Some(scalar),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }
+    }
+    /// The scalar type of the field argument of this type, if any:
+    /// the key of a map, or the path into a JSON object
+    fn field_argument_scalar(&self) -> Option<ClickHouseScalar> {
+        match &self.0 {
+            ClickHouseDataType::Map { .. } => self.map_key_scalar(),
+            ClickHouseDataType::Json(_) => Some(ClickHouseScalar(ClickHouseDataType::String)),
+            _ => None,
+        }
+    }
+    /// The scalar types of the arguments of geo operators, a polygon, or a point and a distance
+    fn geo_argument_scalars(&self) -> Vec<ClickHouseScalar> {
+        match &self.0 {
+            ClickHouseDataType::Point => vec![
+                ClickHouseScalar(ClickHouseDataType::Polygon),
+                ClickHouseScalar(ClickHouseDataType::Float64),
+            ],
+            _ => vec![],
+        }
+    }
+    /// The object types of the arguments of geo operators
+    fn geo_argument_objects(&self) -> Vec<(ObjectTypeName, models::ObjectType)> {
+        match &self.0 {
+            ClickHouseDataType::Point => vec![(
+                POINT_DISTANCE_TYPE.into(),
+                models::ObjectType {
+                    description: Some("A point, and a distance from that point".to_string()),
+                    fields: [
+                        (
+                            "distance".into(),
+                            models::ObjectField {
+                                description: Some("The distance, in meters".to_string()),
+                                r#type: models::Type::Named {
+                                    name: ClickHouseDataType::Float64.to_string().into(),
+                                },
+                                arguments: BTreeMap::new(),
+                            },
+                        ),
+                        (
+                            "point".into(),
+                            models::ObjectField {
+                                description: Some(
+                                    "The point, as [longitude, latitude]".to_string(),
+                                ),
+                                r#type: models::Type::Named {
+                                    name: self.type_name().into_inner(),
+                                },
+                                arguments: BTreeMap::new(),
+                            },
+                        ),
+                    ]
+                    .into_iter()
+                    .collect(),
+                },
+            )],
+            _ => vec![],
+        }
+    }
+    /// The aggregate functions exposed for this type, by name, and their result types.
+    /// Parametric aggregate functions are exposed under the names of their configured variants
+    fn exposed_aggregate_functions(
         &self,
-    ) -> Vec<(ClickHouseSingleColumnAggregateFunction, ClickHouseDataType)> {
+        parameterized_functions: &BTreeMap<AggregateFunctionName, ParameterizedAggregateFunction>,
+    ) -> Vec<(AggregateFunctionName, ClickHouseDataType)> {
+        self.aggregate_functions()
+            .into_iter()
+            .flat_map(|(function, result_type)| match function {
+                CatalogueAggregateFunction::BuiltIn(function) => {
+                    vec![(function.to_string().into(), result_type)]
+                }
+                CatalogueAggregateFunction::Parametric(function) => parameterized_functions
+                    .iter()
+                    .filter(|(_, variant)| variant.parametric_function() == function)
+                    .map(|(function_name, _)| (function_name.to_owned(), result_type.to_owned()))
+                    .collect(),
+            })
+            .collect()
+    }
+    fn aggregate_functions(&self) -> Vec<(CatalogueAggregateFunction, ClickHouseDataType)> {
         use ClickHouseSingleColumnAggregateFunction as AF;
 
-        match self.0 {
-            ClickHouseDataType::Bool => vec![],
-            ClickHouseDataType::String => vec![],
+        let aggregate_functions: Vec<(AF, ClickHouseDataType)> = match self.0 {
+            ClickHouseDataType::Bool => vec![
+                // null when there are no non-null values to aggregate
+                (
+                    AF::BoolAnd,
+                    ClickHouseDataType::Nullable(Box::new(ClickHouseDataType::Bool)),
+                ),
+                (
+                    AF::BoolOr,
+                    ClickHouseDataType::Nullable(Box::new(ClickHouseDataType::Bool)),
+                ),
+                (AF::Any, ClickHouseDataType::Bool),
+                (AF::AnyLast, ClickHouseDataType::Bool),
+            ],
+            ClickHouseDataType::String => vec![
+                (AF::Max, ClickHouseDataType::String),
+                (AF::Min, ClickHouseDataType::String),
+            ],
             ClickHouseDataType::UInt8 => vec![
                 (AF::Max, ClickHouseDataType::UInt8),
                 (AF::Min, ClickHouseDataType::UInt8),
@@ -358,15 +546,127 @@ impl ClickHouseScalar {
             ClickHouseDataType::DateTime64 { .. } => {
                 vec![(AF::Max, self.0.to_owned()), (AF::Min, self.0.to_owned())]
             }
+            ClickHouseDataType::Uuid => vec![
+                (AF::Max, ClickHouseDataType::Uuid),
+                (AF::Min, ClickHouseDataType::Uuid),
+            ],
+            ClickHouseDataType::IPv4 => vec![
+                (AF::Max, ClickHouseDataType::IPv4),
+                (AF::Min, ClickHouseDataType::IPv4),
+            ],
+            ClickHouseDataType::IPv6 => vec![
+                (AF::Max, ClickHouseDataType::IPv6),
+                (AF::Min, ClickHouseDataType::IPv6),
+            ],
+            // enums are ordered by the numeric value of each variant, not by name
+            ClickHouseDataType::Enum(_) => {
+                vec![(AF::Max, self.0.to_owned()), (AF::Min, self.0.to_owned())]
+            }
             _ => vec![],
+        };
+
+        let aggregate_functions: Vec<(CatalogueAggregateFunction, ClickHouseDataType)> =
+            aggregate_functions
+                .into_iter()
+                .map(|(function, result_type)| (function.into(), result_type))
+                .collect();
+
+        match self.0 {
+            ClickHouseDataType::UInt8
+            | ClickHouseDataType::UInt16
+            | ClickHouseDataType::UInt32
+            | ClickHouseDataType::UInt64
+            | ClickHouseDataType::UInt128
+            | ClickHouseDataType::UInt256
+            | ClickHouseDataType::Int8
+            | ClickHouseDataType::Int16
+            | ClickHouseDataType::Int32
+            | ClickHouseDataType::Int64
+            | ClickHouseDataType::Int128
+            | ClickHouseDataType::Int256
+            | ClickHouseDataType::Float32
+            | ClickHouseDataType::Float64
+            | ClickHouseDataType::Decimal { .. }
+            | ClickHouseDataType::Decimal32 { .. }
+            | ClickHouseDataType::Decimal64 { .. }
+            | ClickHouseDataType::Decimal128 { .. }
+            | ClickHouseDataType::Decimal256 { .. } => {
+                [aggregate_functions, self.numeric_aggregate_functions()].concat()
+            }
+            ClickHouseDataType::String
+            | ClickHouseDataType::Uuid
+            | ClickHouseDataType::IPv4
+            | ClickHouseDataType::IPv6
+            | ClickHouseDataType::Enum { .. } => {
+                [aggregate_functions, self.value_aggregate_functions()].concat()
+            }
+            _ => aggregate_functions,
         }
     }
+    /// ClickHouse specific aggregate functions available for all numeric types
+    fn numeric_aggregate_functions(&self) -> Vec<(CatalogueAggregateFunction, ClickHouseDataType)> {
+        use ClickHouseParametricAggregateFunction as PF;
+        use ClickHouseSingleColumnAggregateFunction as AF;
+
+        // quantiles of decimals keep the decimal type, quantiles of other numeric types are interpolated as Float64.
+        // This matches the result types of ClickHouse's quantile function, which median is an alias of
+        let quantile_type = match self.0 {
+            ClickHouseDataType::Decimal { .. }
+            | ClickHouseDataType::Decimal32 { .. }
+            | ClickHouseDataType::Decimal64 { .. }
+            | ClickHouseDataType::Decimal128 { .. }
+            | ClickHouseDataType::Decimal256 { .. } => self.0.to_owned(),
+            _ => ClickHouseDataType::Float64,
+        };
+
+        [
+            vec![
+                (AF::Median.into(), quantile_type.clone()),
+                (AF::Quantile50.into(), quantile_type.clone()),
+                (AF::Quantile90.into(), quantile_type.clone()),
+                (AF::Quantile95.into(), quantile_type.clone()),
+                (AF::Quantile99.into(), quantile_type.clone()),
+                (PF::Quantile.into(), quantile_type),
+            ],
+            self.value_aggregate_functions(),
+        ]
+        .concat()
+    }
+    /// ClickHouse specific aggregate functions that pick, count or collect values, available for numeric and string-like types
+    fn value_aggregate_functions(&self) -> Vec<(CatalogueAggregateFunction, ClickHouseDataType)> {
+        use ClickHouseParametricAggregateFunction as PF;
+        use ClickHouseSingleColumnAggregateFunction as AF;
+
+        let array_of_self = ClickHouseDataType::Array(Box::new(self.0.to_owned()));
+
+        vec![
+            (AF::Uniq.into(), ClickHouseDataType::UInt64),
+            (AF::UniqExact.into(), ClickHouseDataType::UInt64),
+            (AF::Any.into(), self.0.to_owned()),
+            (AF::AnyLast.into(), self.0.to_owned()),
+            (AF::GroupArray.into(), array_of_self.clone()),
+            (AF::GroupUniqArray.into(), array_of_self.clone()),
+            (AF::TopK.into(), array_of_self.clone()),
+            (PF::TopK.into(), array_of_self),
+        ]
+    }
     fn comparison_operators(&self) -> Vec<ClickHouseBinaryComparisonOperator> {
         use ClickHouseBinaryComparisonOperator as BC;
 
         let equality_operators = vec![BC::Eq, BC::NotEq, BC::In, BC::NotIn];
         let ordering_operators = vec![BC::Gt, BC::Lt, BC::GtEq, BC::LtEq];
-        let string_operators = vec![BC::Like, BC::NotLike, BC::ILike, BC::NotILike, BC::Match];
+        let string_operators = vec![
+            BC::Like,
+            BC::NotLike,
+            BC::ILike,
+            BC::NotILike,
+            BC::Match,
+            BC::Contains,
+            BC::IContains,
+            BC::StartsWith,
+            BC::EndsWith,
+            BC::HasToken,
+        ];
 
         match self.0 {
             ClickHouseDataType::Bool => equality_operators,
@@ -405,11 +705,47 @@ impl ClickHouseScalar {
             ClickHouseDataType::IPv4 => [equality_operators, ordering_operators].concat(),
             ClickHouseDataType::IPv6 => [equality_operators, ordering_operators].concat(),
             ClickHouseDataType::Enum { .. } => equality_operators,
+            ClickHouseDataType::Map { .. } => vec![BC::HasKey, BC::HasAnyKey, BC::HasAllKeys],
+            ClickHouseDataType::Point => vec![BC::WithinPolygon, BC::WithinDistance],
             _ => vec![],
         }
     }
 }
 
+/// The name of the argument of map fields, used to look up the value for a single key
+pub const MAP_KEY_ARGUMENT: &str = "key";
+/// The name of the argument of JSON fields, used to select the value at a dot separated path
+pub const JSON_PATH_ARGUMENT: &str = "path";
+/// The name of the object type of the argument to the `_within_distance` operator on points
+pub const POINT_DISTANCE_TYPE: &str = "PointDistance";
+
+/// Aggregate functions may return arrays of scalars, such as `groupArray`,
+/// or nullable scalars, such as `bool_and`
+fn aggregate_result_type(data_type: &ClickHouseDataType) -> models::Type {
+    match data_type {
+        ClickHouseDataType::Array(element_type) => models::Type::Array {
+            element_type: Box::new(aggregate_result_type(element_type)),
+        },
+        ClickHouseDataType::Nullable(inner) => models::Type::Nullable {
+            underlying_type: Box::new(aggregate_result_type(inner)),
+        },
+        _ => models::Type::Named {
+            name: data_type.to_string().into(),
+        },
+    }
+}
+
+/// The scalar type returned by an aggregate function, or the scalar element type when it returns an array,
+/// or the underlying type when it returns a nullable scalar
+fn aggregate_result_scalar(data_type: ClickHouseDataType) -> ClickHouseDataType {
+    match data_type {
+        ClickHouseDataType::Array(inner) | ClickHouseDataType::Nullable(inner) => {
+            aggregate_result_scalar(*inner)
+        }
+        _ => data_type,
+    }
+}
+
 pub enum ClickHouseTypeDefinition {
     Scalar(ClickHouseScalar),
     Nullable {
@@ -594,42 +930,74 @@ impl ClickHouseTypeDefinition {
     }
     /// returns the schema type definitions for this type
     /// note that ScalarType definitions may be duplicated
-    pub fn type_definitions(&self) -> SchemaTypeDefinitions {
+    pub fn type_definitions(
+        &self,
+        parameterized_functions: &BTreeMap<AggregateFunctionName, ParameterizedAggregateFunction>,
+    ) -> SchemaTypeDefinitions {
         match self {
             ClickHouseTypeDefinition::Scalar(scalar) => {
                 // add the definition for this scalar, and any dependencies
                 fn get_dependencies(
-                    aggregate_functions: Vec<(
-                        ClickHouseSingleColumnAggregateFunction,
-                        ClickHouseDataType,
-                    )>,
+                    scalar: &ClickHouseScalar,
+                    parameterized_functions: &BTreeMap<
+                        AggregateFunctionName,
+                        ParameterizedAggregateFunction,
+                    >,
                     scalars: &mut IndexMap<ScalarTypeName, models::ScalarType>,
                 ) {
-                    for (_, return_type) in aggregate_functions {
-                        let return_type = ClickHouseScalar(return_type);
+                    for (_, return_type) in
+                        scalar.exposed_aggregate_functions(parameterized_functions)
+                    {
+                        let return_type = ClickHouseScalar(aggregate_result_scalar(return_type));
                         if !scalars.contains_key(&return_type.type_name()) {
-                            scalars.insert(return_type.type_name(), return_type.type_definition());
-                            get_dependencies(return_type.aggregate_functions(), scalars);
+                            scalars.insert(
+                                return_type.type_name(),
+                                return_type.type_definition(parameterized_functions),
+                            );
+                            get_dependencies(&return_type, parameterized_functions, scalars);
                         }
                     }
                 }
 
                 let mut scalars = IndexMap::new();
 
-                scalars.insert(scalar.type_name(), scalar.type_definition());
+                scalars.insert(
+                    scalar.type_name(),
+                    scalar.type_definition(parameterized_functions),
+                );
+
+                get_dependencies(scalar, parameterized_functions, &mut scalars);
 
-                get_dependencies(scalar.aggregate_functions(), &mut scalars);
+                // map operators and field arguments take arguments of the key type,
+                // geo operators take polygons and distances
+                for argument_scalar in scalar
+                    .field_argument_scalar()
+                    .into_iter()
+                    .chain(scalar.geo_argument_scalars())
+                {
+                    if !scalars.contains_key(&argument_scalar.type_name()) {
+                        scalars.insert(
+                            argument_scalar.type_name(),
+                            argument_scalar.type_definition(parameterized_functions),
+                        );
+                        get_dependencies(&argument_scalar, parameterized_functions, &mut scalars);
+                    }
+                }
 
                 let scalars = scalars.into_iter().collect();
 
                 SchemaTypeDefinitions {
                     scalars,
-                    objects: vec![],
+                    objects: scalar.geo_argument_objects(),
                 }
             }
 
-            ClickHouseTypeDefinition::Nullable { inner } => inner.type_definitions(),
-            ClickHouseTypeDefinition::Array { element_type } => element_type.type_definitions(),
+            ClickHouseTypeDefinition::Nullable { inner } => {
+                inner.type_definitions(parameterized_functions)
+            }
+            ClickHouseTypeDefinition::Array { element_type } => {
+                element_type.type_definitions(parameterized_functions)
+            }
             ClickHouseTypeDefinition::Object {
                 name: namespace,
                 fields,
@@ -642,7 +1010,7 @@ impl ClickHouseTypeDefinition {
                     let SchemaTypeDefinitions {
                         mut scalars,
                         mut objects,
-                    } = field.type_definitions();
+                    } = field.type_definitions(parameterized_functions);
 
                     scalar_type_definitions.append(&mut scalars);
                     object_type_definitions.append(&mut objects);
@@ -672,16 +1040,61 @@ impl ClickHouseTypeDefinition {
             }
         }
     }
+    /// The aggregate functions exposed for this type, by name, and their result types
     pub fn aggregate_functions(
         &self,
-    ) -> Vec<(ClickHouseSingleColumnAggregateFunction, ClickHouseDataType)> {
+        parameterized_functions: &BTreeMap<AggregateFunctionName, ParameterizedAggregateFunction>,
+    ) -> Vec<(AggregateFunctionName, ClickHouseDataType)> {
         match self {
-            ClickHouseTypeDefinition::Scalar(scalar) => scalar.aggregate_functions(),
-            ClickHouseTypeDefinition::Nullable { inner } => inner.aggregate_functions(),
+            ClickHouseTypeDefinition::Scalar(scalar) => {
+                scalar.exposed_aggregate_functions(parameterized_functions)
+            }
+            ClickHouseTypeDefinition::Nullable { inner } => {
+                inner.aggregate_functions(parameterized_functions)
+            }
             ClickHouseTypeDefinition::Array { .. } => vec![],
             ClickHouseTypeDefinition::Object { .. } => vec![],
         }
     }
+    /// the arguments of an object field of this type.
+    /// Map fields take an optional key, to return the value for that key instead of the whole map.
+    /// JSON fields take an optional path, to return the value at that path instead of the whole object
+    pub fn field_arguments(&self) -> BTreeMap<ArgumentName, models::ArgumentInfo> {
+        let scalar = match self {
+            ClickHouseTypeDefinition::Scalar(scalar) => scalar,
+            ClickHouseTypeDefinition::Nullable { .. }
+            | ClickHouseTypeDefinition::Array { .. }
+            | ClickHouseTypeDefinition::Object { .. } => return BTreeMap::new(),
+        };
+
+        let (argument_name, description) = match scalar.0 {
+            ClickHouseDataType::Map { .. } => (
+                MAP_KEY_ARGUMENT,
+                "Return the value for this key, instead of the whole map. \
+                Missing keys return the default value of the value type, such as an empty string or 0",
+            ),
+            ClickHouseDataType::Json(_) => (
+                JSON_PATH_ARGUMENT,
+                "Return the value at this dot separated path, instead of the whole object",
+            ),
+            _ => return BTreeMap::new(),
+        };
+
+        match scalar.field_argument_scalar() {
+            Some(argument_scalar) => BTreeMap::from([(
+                argument_name.into(),
+                models::ArgumentInfo {
+                    description: Some(description.to_string()),
+                    argument_type: models::Type::Nullable {
+                        underlying_type: Box::new(models::Type::Named {
+                            name: argument_scalar.type_name().into_inner(),
+                        }),
+                    },
+                },
+            )]),
+            None => BTreeMap::new(),
+        }
+    }
     /// the underlying non-nullable type, with any wrapping nullable variants removed
     pub fn non_nullable(&self) -> &Self {
         match self {
@@ -697,3 +1110,51 @@ pub struct SchemaTypeDefinitions {
     pub scalars: Vec<(ScalarTypeName, models::ScalarType)>,
     pub objects: Vec<(ObjectTypeName, models::ObjectType)>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quantile_result_types(data_type: &str) -> Vec<(AggregateFunctionName, ClickHouseDataType)> {
+        let scalar = ClickHouseScalar(data_type.parse().expect("Should parse data type"));
+        let parameterized_functions = BTreeMap::from([(
+            "quantile_75".into(),
+            ParameterizedAggregateFunction::Quantile { level: 0.75 },
+        )]);
+
+        scalar
+            .exposed_aggregate_functions(&parameterized_functions)
+            .into_iter()
+            .filter(|(function_name, _)| {
+                function_name.as_str() == "median"
+                    || function_name.as_str().starts_with("quantile_")
+            })
+            .collect()
+    }
+
+    #[test]
+    fn quantiles_of_decimals_keep_the_decimal_type() {
+        let decimal: ClickHouseDataType = "Decimal(10, 2)".parse().unwrap();
+
+        assert_eq!(
+            quantile_result_types("Decimal(10, 2)"),
+            vec![
+                ("median".into(), decimal.clone()),
+                ("quantile_50".into(), decimal.clone()),
+                ("quantile_90".into(), decimal.clone()),
+                ("quantile_95".into(), decimal.clone()),
+                ("quantile_99".into(), decimal.clone()),
+                ("quantile_75".into(), decimal),
+            ]
+        );
+    }
+
+    #[test]
+    fn quantiles_of_integers_and_floats_are_float64() {
+        for data_type in ["UInt32", "Int64", "Float32"] {
+            assert!(quantile_result_types(data_type)
+                .iter()
+                .all(|(_, result_type)| *result_type == ClickHouseDataType::Float64));
+        }
+    }
+}
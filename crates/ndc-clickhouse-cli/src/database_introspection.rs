@@ -12,7 +12,6 @@ pub struct TableInfo {
     #[allow(dead_code)]
     pub table_catalog: String,
     pub table_comment: Option<String>,
-    #[allow(dead_code)]
     pub table_type: TableType,
     pub primary_key: Option<String>,
     pub view_definition: String,
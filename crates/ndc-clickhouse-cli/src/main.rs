@@ -9,7 +9,7 @@ use common::{
         ServerConfigFile, TableConfigFile, CONFIG_FILE_NAME, CONFIG_SCHEMA_FILE_NAME,
     },
 };
-use database_introspection::{introspect_database, TableInfo};
+use database_introspection::{introspect_database, TableInfo, TableType};
 use ndc_clickhouse_core::connector::setup::ClickhouseConnectorSetup;
 use ndc_models::{CollectionName, FieldName};
 use ndc_sdk_core::schema::print_schema_and_capabilities;
@@ -240,12 +240,20 @@ async fn update_tables_config(
                         .collect(),
                 }),
                 arguments,
+                view: Some(matches!(table.table_type, TableType::View)),
                 return_type: get_table_return_type(
                     table,
                     &old_table_config,
                     &old_config,
                     introspection,
                 ),
+                // mutation settings are user configuration, preserved through updates
+                destructive_procedures: old_table_config
+                    .is_some_and(|(_, old_table)| old_table.destructive_procedures),
+                mutations_sync: old_table_config
+                    .and_then(|(_, old_table)| old_table.mutations_sync),
+                async_insert: old_table_config
+                    .and_then(|(_, old_table)| old_table.async_insert.to_owned()),
             };
 
             (table_alias, table_config)
@@ -259,6 +267,10 @@ async fn update_tables_config(
             .as_ref()
             .map(|old_config| old_config.queries.to_owned())
             .unwrap_or_default(),
+        aggregate_functions: old_config
+            .as_ref()
+            .map(|old_config| old_config.aggregate_functions.to_owned())
+            .unwrap_or_default(),
     };
     let config_schema = schema_for!(ServerConfigFile);
 
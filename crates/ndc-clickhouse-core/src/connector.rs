@@ -5,10 +5,9 @@ pub mod state;
 use self::state::ServerState;
 use async_trait::async_trait;
 use common::{capabilities::capabilities, config::ServerConfig, schema::schema_response};
-use http::StatusCode;
 use ndc_models as models;
 use ndc_sdk_core::{
-    connector::{Connector, ErrorResponse, Result},
+    connector::{Connector, Result},
     json_response::JsonResponse,
 };
 
@@ -43,27 +42,19 @@ impl Connector for ClickhouseConnector {
     }
 
     async fn mutation_explain(
-        _configuration: &Self::Configuration,
-        _state: &Self::State,
-        _request: models::MutationRequest,
+        configuration: &Self::Configuration,
+        state: &Self::State,
+        request: models::MutationRequest,
     ) -> Result<JsonResponse<models::ExplainResponse>> {
-        Err(ErrorResponse::new(
-            StatusCode::NOT_IMPLEMENTED,
-            "mutation explain not supported".to_string(),
-            serde_json::Value::Null,
-        ))
+        handler::mutation_explain(configuration, state, request).await
     }
 
     async fn mutation(
-        _configuration: &Self::Configuration,
-        _state: &Self::State,
-        _request: models::MutationRequest,
+        configuration: &Self::Configuration,
+        state: &Self::State,
+        request: models::MutationRequest,
     ) -> Result<JsonResponse<models::MutationResponse>> {
-        Err(ErrorResponse::new(
-            StatusCode::NOT_IMPLEMENTED,
-            "mutation not supported".to_string(),
-            serde_json::Value::Null,
-        ))
+        handler::mutation(configuration, state, request).await
     }
 
     async fn query(
@@ -1,5 +1,7 @@
 mod explain;
+mod mutation;
 mod query;
 
 pub use explain::*;
+pub use mutation::*;
 pub use query::*;
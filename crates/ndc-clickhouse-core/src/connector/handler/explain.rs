@@ -4,7 +4,10 @@ use ndc_sdk_core::{connector::ErrorResponse, json_response::JsonResponse};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
-use crate::{connector::state::ServerState, sql::QueryBuilder};
+use crate::{
+    connector::state::ServerState,
+    sql::{ast::MutationStatement, MutationBuilder, QueryBuilder},
+};
 
 #[derive(Debug, Serialize, Deserialize)]
 struct ExplainRow {
@@ -57,6 +60,114 @@ pub async fn explain(
     Ok(JsonResponse::Value(models::ExplainResponse { details }))
 }
 
+pub async fn mutation_explain(
+    configuration: &ServerConfig,
+    state: &ServerState,
+    request: models::MutationRequest,
+) -> Result<JsonResponse<models::ExplainResponse>, ErrorResponse> {
+    let client = state
+        .client(configuration)
+        .await
+        .map_err(ErrorResponse::from_error)?;
+
+    let MutationExplain {
+        mut details,
+        execution_plans,
+    } = mutation_explain_details(configuration, &request)?;
+
+    for ExecutionPlanStatement {
+        key,
+        statement,
+        parameters,
+    } in execution_plans
+    {
+        let explain = execute_text_query::<ExplainRow>(
+            &client,
+            &configuration.connection,
+            &statement,
+            &parameters,
+        )
+        .await
+        .unwrap_or_else(|err| err.to_string());
+
+        details.insert(key, explain);
+    }
+
+    Ok(JsonResponse::Value(models::ExplainResponse { details }))
+}
+
+/// The generated statements for each operation in a mutation request,
+/// and the `EXPLAIN` statements to execute for operations that have an execution plan
+#[derive(Debug)]
+pub struct MutationExplain {
+    pub details: BTreeMap<String, String>,
+    pub execution_plans: Vec<ExecutionPlanStatement>,
+}
+
+#[derive(Debug)]
+pub struct ExecutionPlanStatement {
+    /// The key to insert the execution plan under in the explain details
+    pub key: String,
+    pub statement: String,
+    pub parameters: Vec<(String, String)>,
+}
+
+pub fn mutation_explain_details(
+    configuration: &ServerConfig,
+    request: &models::MutationRequest,
+) -> Result<MutationExplain, ErrorResponse> {
+    let mut details = BTreeMap::new();
+    let mut execution_plans = vec![];
+
+    // details are sorted by key, so operation indexes are zero-padded to keep operations in order
+    let index_width = request.operations.len().saturating_sub(1).to_string().len();
+
+    for (index, operation) in request.operations.iter().enumerate() {
+        let key = |label: &str| format!("Operation {index:0index_width$}: {label}");
+
+        let builder =
+            MutationBuilder::new(operation, &request.collection_relationships, configuration);
+
+        let inlined_statement = builder.build_inlined()?;
+        let (parameterized_statement, parameters) = builder.build_parameterized()?;
+
+        details.insert(
+            key("SQL Mutation"),
+            print_mutation_statement(&inlined_statement),
+        );
+        details.insert(
+            key("Parameterized SQL Mutation"),
+            print_mutation_statement(&parameterized_statement),
+        );
+        details.insert(
+            key("Parameters"),
+            serde_json::to_string(&parameters).map_err(ErrorResponse::from_error)?,
+        );
+
+        // INSERT, DELETE, and ALTER statements are not executed, as that would modify data
+        if let Some(explain_statement) = parameterized_statement.explain() {
+            execution_plans.push(ExecutionPlanStatement {
+                key: key("Execution Plan"),
+                statement: explain_statement.to_string(),
+                parameters,
+            });
+        }
+    }
+
+    Ok(MutationExplain {
+        details,
+        execution_plans,
+    })
+}
+
+fn print_mutation_statement(statement: &MutationStatement) -> String {
+    match statement {
+        // insert statements are followed by the rows to insert, which should not be formatted as SQL
+        MutationStatement::Insert(_) => statement.to_string(),
+        _ => pretty_print_sql(&statement.to_string()),
+    }
+}
+
 fn pretty_print_sql(query: &str) -> String {
     use sqlformat::{format, FormatOptions, Indent, QueryParams};
     let params = QueryParams::None;
@@ -0,0 +1,78 @@
+use common::{client::execute_mutation_query, config::ServerConfig};
+use ndc_models as models;
+use ndc_sdk_core::{connector::ErrorResponse, json_response::JsonResponse};
+use tracing::Instrument;
+
+use crate::{connector::state::ServerState, sql::MutationBuilder};
+
+pub async fn mutation(
+    configuration: &ServerConfig,
+    state: &ServerState,
+    request: models::MutationRequest,
+) -> Result<JsonResponse<models::MutationResponse>, ErrorResponse> {
+    #[cfg(debug_assertions)]
+    {
+        use tracing::Level;
+        // this block only present in debug builds, to avoid leaking sensitive information
+        let request_string = serde_json::to_string(&request).map_err(ErrorResponse::from_error)?;
+
+        tracing::event!(Level::DEBUG, "Incoming IR" = request_string);
+    }
+
+    let client = state
+        .client(configuration)
+        .await
+        .map_err(ErrorResponse::from_error)?;
+
+    let mut operation_results = vec![];
+
+    // operations are executed in order. ClickHouse has no transactions,
+    // so operations that succeeded before a failing operation are not rolled back
+    for operation in &request.operations {
+        let builder =
+            MutationBuilder::new(operation, &request.collection_relationships, configuration);
+
+        let (statement_string, parameters) =
+            tracing::info_span!("Build SQL Mutation", internal.visibility = "user").in_scope(
+                || -> Result<_, ErrorResponse> {
+                    let (statement, parameters) = builder.build_parameterized()?;
+
+                    #[cfg(debug_assertions)]
+                    {
+                        use tracing::Level;
+                        // this block only present in debug builds, to avoid leaking sensitive information
+                        let unsafe_statement_string = builder.build_inlined()?.to_string();
+
+                        tracing::event!(Level::DEBUG, "Generated SQL" = unsafe_statement_string);
+                    }
+
+                    Ok((statement.to_string(), parameters))
+                },
+            )?;
+
+        let execution_span = tracing::info_span!(
+            "Execute SQL mutation",
+            db.system = "clickhouse",
+            db.user = configuration.connection.username,
+            internal.visibility = "user",
+        );
+
+        let (response, summary) = execute_mutation_query(
+            &client,
+            &configuration.connection,
+            &statement_string,
+            &parameters,
+        )
+        .instrument(execution_span)
+        .await
+        .map_err(ErrorResponse::from_error)?;
+
+        let result = builder.procedure_result(&response, summary.as_ref())?;
+
+        operation_results.push(models::MutationOperationResults::Procedure { result });
+    }
+
+    Ok(JsonResponse::Value(models::MutationResponse {
+        operation_results,
+    }))
+}
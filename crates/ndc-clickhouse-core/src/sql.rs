@@ -1,3 +1,5 @@
 pub mod ast;
+mod mutation_builder;
 mod query_builder;
+pub use mutation_builder::MutationBuilder;
 pub use query_builder::{QueryBuilder, QueryBuilderError};
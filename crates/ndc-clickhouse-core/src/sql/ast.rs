@@ -5,7 +5,7 @@ use common::{
     clickhouse_parser::{datatype::ClickHouseDataType, parameterized_query::ParameterType},
     format::{display_comma_separated, display_period_separated, display_separated},
 };
-use format::escape_string;
+use format::{escape_identifier, escape_string};
 use indexmap::IndexMap;
 
 #[derive(Debug, Clone)]
@@ -53,9 +53,161 @@ impl fmt::Display for Statement {
     }
 }
 
+/// A statement that writes data
+#[derive(Debug, Clone)]
+pub enum MutationStatement {
+    Insert(Insert),
+    Delete(Delete),
+    Update(Update),
+    Query(Statement),
+    /// A native query executed as is, such as `INSERT INTO ... SELECT`
+    NativeQuery(NativeQuery),
+}
+
+impl MutationStatement {
+    /// The `EXPLAIN` statement for this mutation, if ClickHouse can produce an execution plan for it.
+    /// Only `SELECT` statements have execution plans
+    pub fn explain(self) -> Option<Statement> {
+        match self {
+            MutationStatement::Query(statement) => Some(statement.explain()),
+            MutationStatement::Insert(_)
+            | MutationStatement::Delete(_)
+            | MutationStatement::Update(_)
+            | MutationStatement::NativeQuery(_) => None,
+        }
+    }
+}
+
+impl fmt::Display for MutationStatement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MutationStatement::Insert(insert) => write!(f, "{insert}"),
+            MutationStatement::Delete(delete) => write!(f, "{delete}"),
+            MutationStatement::Update(update) => write!(f, "{update}"),
+            MutationStatement::Query(statement) => write!(f, "{statement}"),
+            MutationStatement::NativeQuery(native_query) => write!(f, "{native_query}"),
+        }
+    }
+}
+
+/// `INSERT INTO table (columns) FORMAT format`, followed by the data to insert.
+/// Note the statement is not terminated with a semicolon, as the data follows the format clause
+#[derive(Debug, Clone)]
+pub struct Insert {
+    table: ObjectName,
+    columns: Vec<Ident>,
+    format: String,
+    rows: Vec<serde_json::Value>,
+}
+
+impl Insert {
+    pub fn new(table: ObjectName, columns: Vec<Ident>) -> Self {
+        Self {
+            table,
+            columns,
+            format: "JSONEachRow".to_string(),
+            rows: vec![],
+        }
+    }
+    pub fn rows(self, rows: Vec<serde_json::Value>) -> Self {
+        Self { rows, ..self }
+    }
+    pub fn into_statement(self) -> MutationStatement {
+        MutationStatement::Insert(self)
+    }
+}
+
+impl fmt::Display for Insert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "INSERT INTO {}", self.table)?;
+        if !self.columns.is_empty() {
+            write!(f, " ({})", display_comma_separated(&self.columns))?;
+        }
+        write!(f, " FORMAT {}", self.format)?;
+        for row in &self.rows {
+            write!(f, "\n{row}")?;
+        }
+        Ok(())
+    }
+}
+
+/// `DELETE FROM table WHERE predicate`, a lightweight delete
+#[derive(Debug, Clone)]
+pub struct Delete {
+    table: ObjectName,
+    predicate: Expr,
+}
+
+impl Delete {
+    pub fn new(table: ObjectName, predicate: Expr) -> Self {
+        Self { table, predicate }
+    }
+    pub fn into_statement(self) -> MutationStatement {
+        MutationStatement::Delete(self)
+    }
+}
+
+impl fmt::Display for Delete {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "DELETE FROM {} WHERE {}", self.table, self.predicate)
+    }
+}
+
+/// `ALTER TABLE table UPDATE assignments WHERE predicate`, a mutation rewriting the affected parts
+#[derive(Debug, Clone)]
+pub struct Update {
+    table: ObjectName,
+    assignments: Vec<Assignment>,
+    predicate: Expr,
+}
+
+impl Update {
+    pub fn new(table: ObjectName, assignments: Vec<Assignment>, predicate: Expr) -> Self {
+        Self {
+            table,
+            assignments,
+            predicate,
+        }
+    }
+    pub fn into_statement(self) -> MutationStatement {
+        MutationStatement::Update(self)
+    }
+}
+
+impl fmt::Display for Update {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "ALTER TABLE {} UPDATE {} WHERE {}",
+            self.table,
+            display_comma_separated(&self.assignments),
+            self.predicate
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Assignment {
+    column: Ident,
+    value: Expr,
+}
+
+impl Assignment {
+    pub fn new(column: Ident, value: Expr) -> Self {
+        Self { column, value }
+    }
+}
+
+impl fmt::Display for Assignment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} = {}", self.column, self.value)
+    }
+}
+
 #[derive(Debug, Default, Clone)]
 pub struct Query {
     with: Vec<WithItem>,
+    distinct: bool,
     select: Vec<SelectItem>,
     from: Vec<TableWithJoins>,
     predicate: Option<Expr>,
@@ -73,6 +225,9 @@ impl Query {
     pub fn with(self, with: Vec<WithItem>) -> Self {
         Self { with, ..self }
     }
+    pub fn distinct(self, distinct: bool) -> Self {
+        Self { distinct, ..self }
+    }
     pub fn select(self, select: Vec<SelectItem>) -> Self {
         Self { select, ..self }
     }
@@ -123,7 +278,12 @@ impl fmt::Display for Query {
         if !self.with.is_empty() {
             write!(f, "WITH {} ", display_comma_separated(&self.with))?;
         }
-        write!(f, "SELECT {}", display_comma_separated(&self.select))?;
+        write!(
+            f,
+            "SELECT {}{}",
+            if self.distinct { "DISTINCT " } else { "" },
+            display_comma_separated(&self.select)
+        )?;
         if !self.from.is_empty() {
             write!(f, " FROM {}", display_comma_separated(&self.from))?;
         }
@@ -157,6 +317,16 @@ impl fmt::Display for Query {
 pub enum WithItem {
     Expr { expr: Expr, alias: Ident },
     Query { query: Box<Query>, alias: Ident },
+    UnionAll { queries: Vec<Query>, alias: Ident },
+}
+
+impl WithItem {
+    pub fn union_all<S: Into<String>>(queries: Vec<Query>, alias: S) -> Self {
+        WithItem::UnionAll {
+            queries,
+            alias: Ident::new_quoted(alias),
+        }
+    }
 }
 
 impl fmt::Display for WithItem {
@@ -164,6 +334,11 @@ impl fmt::Display for WithItem {
         match self {
             WithItem::Expr { expr, alias } => write!(f, "{expr} AS {alias}"),
             WithItem::Query { query, alias } => write!(f, "{alias} AS ({query})"),
+            WithItem::UnionAll { queries, alias } => write!(
+                f,
+                "{alias} AS ({})",
+                display_separated(queries, " UNION ALL ", |f, query| write!(f, "{query}"))
+            ),
         }
     }
 }
@@ -432,6 +607,9 @@ impl NativeQuery {
             alias: None,
         }
     }
+    pub fn into_mutation_statement(self) -> MutationStatement {
+        MutationStatement::NativeQuery(self)
+    }
 }
 
 impl fmt::Display for NativeQuery {
@@ -471,6 +649,7 @@ impl ObjectName {
     pub fn into_table_function(self) -> Function {
         Function {
             name: self,
+            parameters: vec![],
             args: vec![],
             over: None,
             distinct: false,
@@ -500,6 +679,11 @@ pub enum Expr {
     Function(Function),
     Lambda(Lambda),
     List(Vec<Expr>),
+    /// Element access, such as a map key lookup: `expr[subscript]`
+    Subscript {
+        expr: Box<Expr>,
+        subscript: Box<Expr>,
+    },
 }
 
 impl Expr {
@@ -542,6 +726,7 @@ impl fmt::Display for Expr {
             Expr::Function(function) => write!(f, "{}", function),
             Expr::Lambda(lambda) => write!(f, "{}", lambda),
             Expr::List(list) => write!(f, "({})", display_comma_separated(list)),
+            Expr::Subscript { expr, subscript } => write!(f, "{expr}[{subscript}]"),
         }
     }
 }
@@ -599,6 +784,8 @@ impl fmt::Display for Lambda {
 #[derive(Debug, Clone)]
 pub struct Function {
     pub name: ObjectName,
+    /// parameters of parametric aggregate functions, such as the level in `quantile(0.9)(column)`
+    pub parameters: Vec<FunctionArg>,
     pub args: Vec<FunctionArg>,
     pub over: Option<WindowSpec>,
     pub distinct: bool,
@@ -608,6 +795,7 @@ impl Function {
     pub fn new_quoted<N: Into<String>>(name: N) -> Self {
         Function {
             name: ObjectName(vec![Ident::new_quoted(name)]),
+            parameters: vec![],
             args: vec![],
             over: None,
             distinct: false,
@@ -616,11 +804,15 @@ impl Function {
     pub fn new_unquoted<N: Into<String>>(name: N) -> Self {
         Function {
             name: ObjectName(vec![Ident::new_unquoted(name)]),
+            parameters: vec![],
             args: vec![],
             over: None,
             distinct: false,
         }
     }
+    pub fn parameters(self, parameters: Vec<FunctionArg>) -> Self {
+        Self { parameters, ..self }
+    }
     pub fn args(self, args: Vec<FunctionArg>) -> Self {
         Self { args, ..self }
     }
@@ -643,10 +835,13 @@ impl Function {
 
 impl fmt::Display for Function {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)?;
+        if !self.parameters.is_empty() {
+            write!(f, "({})", display_comma_separated(&self.parameters))?;
+        }
         write!(
             f,
-            "{}({}{})",
-            self.name,
+            "({}{})",
             if self.distinct { "DISTINCT " } else { "" },
             display_comma_separated(&self.args)
         )?;
@@ -839,10 +1034,37 @@ impl Value {
             }
         }
 
+        /// geo types are aliases for tuples and arrays of points
+        fn geo_underlying_type(data_type: &ClickHouseDataType) -> Option<ClickHouseDataType> {
+            match data_type {
+                ClickHouseDataType::Point => Some(ClickHouseDataType::Tuple(vec![
+                    (None, ClickHouseDataType::Float64),
+                    (None, ClickHouseDataType::Float64),
+                ])),
+                ClickHouseDataType::Ring | ClickHouseDataType::LineString => Some(
+                    ClickHouseDataType::Array(Box::new(ClickHouseDataType::Point)),
+                ),
+                ClickHouseDataType::MultiLineString => Some(ClickHouseDataType::Array(Box::new(
+                    ClickHouseDataType::LineString,
+                ))),
+                ClickHouseDataType::Polygon => Some(ClickHouseDataType::Array(Box::new(
+                    ClickHouseDataType::Ring,
+                ))),
+                ClickHouseDataType::MultiPolygon => Some(ClickHouseDataType::Array(Box::new(
+                    ClickHouseDataType::Polygon,
+                ))),
+                _ => None,
+            }
+        }
+
         fn map_json_value(
             value: &serde_json::Value,
             data_type: &ClickHouseDataType,
         ) -> Result<Value, QueryBuilderError> {
+            if let Some(geo_type) = geo_underlying_type(underlying_type(data_type)) {
+                return map_json_value(value, &geo_type);
+            }
+
             match value {
                 serde_json::Value::Null => Ok(Value::Null),
                 serde_json::Value::Bool(b) => Ok(Value::Boolean(b.to_owned())),
@@ -922,6 +1144,8 @@ impl Value {
                             })
                             .collect::<Result<IndexMap<_, _>, QueryBuilderError>>()?,
                     )),
+                    // json objects are passed as json strings, which clickhouse parses into the JSON type
+                    ClickHouseDataType::Json(_) => Ok(Value::SingleQuotedString(value.to_string())),
                     ClickHouseDataType::Tuple(elements) => {
                         // tuple should be named, and all tuple keys should be in the input object
                         Ok(Value::Tuple(
@@ -1048,7 +1272,7 @@ impl From<&Ident> for Ident {
 impl fmt::Display for Ident {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         if self.quoted {
-            write!(f, "\"{}\"", self.value)
+            write!(f, "\"{}\"", escape_identifier(&self.value))
         } else {
             write!(f, "{}", self.value)
         }
@@ -22,6 +22,28 @@ pub fn escape_string(s: &str) -> EscapedString {
     EscapedString(s)
 }
 
+pub struct EscapedIdentifier<'a>(&'a str);
+impl fmt::Display for EscapedIdentifier<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '\t' => write!(f, "\\t")?,
+                '\n' => write!(f, "\\n")?,
+                '\r' => write!(f, "\\r")?,
+                '"' => write!(f, "\\\"")?,
+                '\\' => write!(f, "\\\\")?,
+                _ => write!(f, "{c}")?,
+            }
+        }
+        Ok(())
+    }
+}
+/// quoted identifiers follow the same escaping rules as string literals, with double quotes escaped instead of single quotes
+/// docs: https://clickhouse.com/docs/en/sql-reference/syntax#identifiers
+pub fn escape_identifier(s: &str) -> EscapedIdentifier {
+    EscapedIdentifier(s)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -39,4 +61,20 @@ mod tests {
             assert_eq!(escape_string(raw).to_string().as_str(), escaped)
         }
     }
+
+    #[test]
+    fn can_escape_identifier() {
+        let test_cases = vec![
+            ("", ""),
+            ("foo", "foo"),
+            ("foo.bar", "foo.bar"),
+            ("it's", "it's"),
+            ("foo\nbar", "foo\\nbar"),
+            ("\\\n\t\r\"", "\\\\\\n\\t\\r\\\""),
+        ];
+
+        for (raw, escaped) in test_cases {
+            assert_eq!(escape_identifier(raw).to_string().as_str(), escaped)
+        }
+    }
 }
@@ -0,0 +1,926 @@
+use super::{ast::*, query_builder::parameter::ParameterBuilder, QueryBuilder, QueryBuilderError};
+use common::{
+    clickhouse_parser::{
+        datatype::{ClickHouseDataType, Identifier},
+        parameterized_query::ParameterizedQueryElement,
+    },
+    client::QuerySummary,
+    config::{ParameterizedQueryConfig, ServerConfig, TableConfig},
+    config_file::MutationsSync,
+    schema::{
+        procedure::{
+            ClickHouseProcedure, AFFECTED_ROWS_FIELD, COMPLETED_FIELD, FLUSHED_FIELD,
+            INSERT_ROWS_ARGUMENT, PREDICATE_ARGUMENT, SUBMITTED_ROWS_FIELD, UPDATE_SET_ARGUMENT,
+            WRITTEN_BYTES_FIELD, WRITTEN_ROWS_FIELD,
+        },
+        type_definition::ClickHouseTypeDefinition,
+    },
+};
+use ndc_models::{self as models, ArgumentName, CollectionName, FieldName, RelationshipName};
+use std::collections::{BTreeMap, BTreeSet};
+
+pub struct MutationBuilder<'r, 'c> {
+    operation: &'r models::MutationOperation,
+    collection_relationships: &'r BTreeMap<RelationshipName, models::Relationship>,
+    configuration: &'c ServerConfig,
+}
+
+type Parameters = Vec<(String, String)>;
+
+/// The procedure invoked by a mutation operation, along with its arguments and requested fields
+struct ProcedureCall<'r, 'c> {
+    procedure: ClickHouseProcedure<'c>,
+    arguments: &'r BTreeMap<ArgumentName, serde_json::Value>,
+    fields: &'r Option<models::NestedField>,
+}
+
+impl<'r, 'c> MutationBuilder<'r, 'c> {
+    pub fn new(
+        operation: &'r models::MutationOperation,
+        collection_relationships: &'r BTreeMap<RelationshipName, models::Relationship>,
+        configuration: &'c ServerConfig,
+    ) -> Self {
+        Self {
+            operation,
+            collection_relationships,
+            configuration,
+        }
+    }
+    /// Build the statement for this operation.
+    /// The returned parameters include any settings that should be passed along with the statement
+    pub fn build_parameterized(
+        &self,
+    ) -> Result<(MutationStatement, Parameters), QueryBuilderError> {
+        let mut parameters = ParameterBuilder::new(false);
+        let statement = self.statement(&mut parameters)?;
+
+        let parameters = parameters
+            .into_parameters()
+            .into_iter()
+            .chain(self.settings()?)
+            .collect();
+
+        Ok((statement, parameters))
+    }
+    pub fn build_inlined(&self) -> Result<MutationStatement, QueryBuilderError> {
+        let mut parameters = ParameterBuilder::new(true);
+        let statement = self.statement(&mut parameters)?;
+
+        Ok(statement)
+    }
+    /// Shape the procedure result, given the response body and summary from the database
+    pub fn procedure_result(
+        &self,
+        response: &[u8],
+        summary: Option<&QuerySummary>,
+    ) -> Result<serde_json::Value, QueryBuilderError> {
+        let ProcedureCall {
+            procedure,
+            arguments,
+            fields,
+        } = self.procedure()?;
+
+        let result = match procedure {
+            ClickHouseProcedure::Insert { table, .. } => {
+                let rows = self.insert_rows(&procedure, arguments)?;
+
+                // without waiting for asynchronous inserts, the server acknowledges the rows once buffered
+                let flushed = match &table.async_insert {
+                    Some(async_insert) => async_insert.wait_for_async_insert,
+                    None => true,
+                };
+
+                serde_json::Value::Object(serde_json::Map::from_iter([
+                    // 64 bit integers are represented as strings
+                    (
+                        AFFECTED_ROWS_FIELD.to_string(),
+                        required_summary(summary)?.written_rows.to_string().into(),
+                    ),
+                    (FLUSHED_FIELD.to_string(), flushed.into()),
+                    (SUBMITTED_ROWS_FIELD.to_string(), rows.to_owned().into()),
+                ]))
+            }
+            // lightweight deletes return once the rows are marked as deleted, but do not report how many
+            ClickHouseProcedure::DeleteWhere { .. } => serde_json::Value::Object(
+                serde_json::Map::from_iter([(COMPLETED_FIELD.to_string(), true.into())]),
+            ),
+            // update mutations do not report how many rows they changed either,
+            // and may still be running unless configured to wait for completion
+            ClickHouseProcedure::UpdateWhere { table, .. } => {
+                let completed = matches!(
+                    table.mutations_sync,
+                    Some(MutationsSync::Local | MutationsSync::AllReplicas)
+                );
+
+                serde_json::Value::Object(serde_json::Map::from_iter([(
+                    COMPLETED_FIELD.to_string(),
+                    completed.into(),
+                )]))
+            }
+            ClickHouseProcedure::InsertSelect { .. } => {
+                let summary = required_summary(summary)?;
+
+                serde_json::Value::Object(serde_json::Map::from_iter([
+                    (
+                        WRITTEN_ROWS_FIELD.to_string(),
+                        summary.written_rows.to_string().into(),
+                    ),
+                    (
+                        WRITTEN_BYTES_FIELD.to_string(),
+                        summary.written_bytes.to_string().into(),
+                    ),
+                ]))
+            }
+            // the statement for native queries returns the rows as a JSON array
+            ClickHouseProcedure::NativeQuery { .. } => {
+                serde_json::from_slice(response).map_err(|err| {
+                    QueryBuilderError::Unexpected(format!(
+                        "unable to parse native query response: {err}"
+                    ))
+                })?
+            }
+        };
+
+        project_fields(result, fields.as_ref())
+    }
+    fn procedure(&self) -> Result<ProcedureCall<'r, 'c>, QueryBuilderError> {
+        match self.operation {
+            models::MutationOperation::Procedure {
+                name,
+                arguments,
+                fields,
+            } => {
+                let procedure = ClickHouseProcedure::from_name(name, self.configuration)
+                    .ok_or_else(|| QueryBuilderError::UnknownProcedure(name.to_owned()))?;
+
+                Ok(ProcedureCall {
+                    procedure,
+                    arguments,
+                    fields,
+                })
+            }
+        }
+    }
+    /// Settings passed as query parameters alongside the statement
+    fn settings(&self) -> Result<Parameters, QueryBuilderError> {
+        let ProcedureCall { procedure, .. } = self.procedure()?;
+
+        let settings = match procedure {
+            // the summary header is only complete once the query has finished
+            ClickHouseProcedure::Insert { table, .. } => {
+                let mut settings = vec![("wait_end_of_query", "1".to_string())];
+                if let Some(async_insert) = &table.async_insert {
+                    settings.push(("async_insert", "1".to_string()));
+                    settings.push((
+                        "wait_for_async_insert",
+                        if async_insert.wait_for_async_insert {
+                            "1"
+                        } else {
+                            "0"
+                        }
+                        .to_string(),
+                    ));
+                    if let Some(busy_timeout_ms) = async_insert.busy_timeout_ms {
+                        settings
+                            .push(("async_insert_busy_timeout_ms", busy_timeout_ms.to_string()));
+                    }
+                }
+                settings
+            }
+            ClickHouseProcedure::DeleteWhere { .. } | ClickHouseProcedure::InsertSelect { .. } => {
+                vec![("wait_end_of_query", "1".to_string())]
+            }
+            ClickHouseProcedure::UpdateWhere { table, .. } => {
+                let mut settings = vec![("wait_end_of_query", "1".to_string())];
+                if let Some(mutations_sync) = table.mutations_sync {
+                    settings.push(("mutations_sync", mutations_sync.setting_value().to_string()));
+                }
+                settings
+            }
+            ClickHouseProcedure::NativeQuery { .. } => vec![],
+        };
+
+        Ok(settings
+            .into_iter()
+            .map(|(name, value)| (name.to_owned(), value))
+            .collect())
+    }
+    fn statement(
+        &self,
+        parameters: &mut ParameterBuilder,
+    ) -> Result<MutationStatement, QueryBuilderError> {
+        let ProcedureCall {
+            procedure,
+            arguments,
+            ..
+        } = self.procedure()?;
+
+        match procedure {
+            ClickHouseProcedure::Insert { table, .. } => {
+                check_arguments(&procedure, arguments, &[INSERT_ROWS_ARGUMENT])?;
+                let rows = self.insert_rows(&procedure, arguments)?;
+
+                let table_type = self
+                    .configuration
+                    .table_types
+                    .get(&table.return_type)
+                    .ok_or_else(|| {
+                        QueryBuilderError::UnknownTableType(table.return_type.to_owned())
+                    })?;
+
+                // only list columns present in the rows: the table may have MATERIALIZED or ALIAS columns,
+                // which cannot be inserted into. Columns missing from some rows take their default value
+                let mut row_columns = BTreeSet::new();
+
+                for row in rows {
+                    if let Some(row) = row.as_object() {
+                        for column_alias in row.keys() {
+                            let column_alias: FieldName = column_alias.to_owned().into();
+                            if !table_type.columns.contains_key(&column_alias) {
+                                return Err(QueryBuilderError::UnknownColumn(
+                                    column_alias,
+                                    table.return_type.to_owned(),
+                                ));
+                            }
+                            row_columns.insert(column_alias);
+                        }
+                    } else {
+                        return Err(QueryBuilderError::InvalidProcedureArgument {
+                            procedure: procedure.name(),
+                            argument: INSERT_ROWS_ARGUMENT.into(),
+                            message: "expected each row to be an object".to_string(),
+                        });
+                    }
+                }
+
+                let columns = row_columns
+                    .iter()
+                    .map(|column_alias| Ident::new_quoted(column_alias.to_string()))
+                    .collect();
+
+                Ok(Insert::new(table_name(table), columns)
+                    .rows(rows.to_owned())
+                    .into_statement())
+            }
+            ClickHouseProcedure::DeleteWhere { table_alias, table } => {
+                check_arguments(&procedure, arguments, &[PREDICATE_ARGUMENT])?;
+                let predicate = self.predicate(&procedure, table_alias, arguments, parameters)?;
+
+                Ok(Delete::new(table_name(table), predicate).into_statement())
+            }
+            ClickHouseProcedure::UpdateWhere { table_alias, table } => {
+                check_arguments(
+                    &procedure,
+                    arguments,
+                    &[PREDICATE_ARGUMENT, UPDATE_SET_ARGUMENT],
+                )?;
+                let assignments = self.assignments(&procedure, table, arguments, parameters)?;
+                let predicate = self.predicate(&procedure, table_alias, arguments, parameters)?;
+
+                Ok(Update::new(table_name(table), assignments, predicate).into_statement())
+            }
+            ClickHouseProcedure::NativeQuery { query, .. } => Ok(MutationStatement::Query(
+                self.native_query(&procedure, query, arguments, parameters)?,
+            )),
+            ClickHouseProcedure::InsertSelect { query, .. } => Ok(NativeQuery::new(
+                native_query_elements(&procedure, query, arguments, parameters)?,
+            )
+            .into_mutation_statement()),
+        }
+    }
+    /// Execute the native query, and aggregate the rows into a JSON array,
+    /// casting each row to the query's return type
+    fn native_query(
+        &self,
+        procedure: &ClickHouseProcedure,
+        query: &ParameterizedQueryConfig,
+        arguments: &BTreeMap<ArgumentName, serde_json::Value>,
+        parameters: &mut ParameterBuilder,
+    ) -> Result<Statement, QueryBuilderError> {
+        let elements = native_query_elements(procedure, query, arguments, parameters)?;
+
+        let table_type = self
+            .configuration
+            .table_types
+            .get(&query.return_type)
+            .ok_or_else(|| QueryBuilderError::UnknownTableType(query.return_type.to_owned()))?;
+
+        let row_cast_type = ClickHouseDataType::Tuple(
+            table_type
+                .columns
+                .iter()
+                .map(|(column_alias, data_type)| {
+                    let type_definition = ClickHouseTypeDefinition::from_query_return_type(
+                        data_type,
+                        column_alias.inner(),
+                        query.return_type.inner().inner(),
+                        &self.configuration.namespace_separator,
+                    );
+                    (
+                        Some(Identifier::DoubleQuoted(column_alias.to_string())),
+                        type_definition.cast_type(),
+                    )
+                })
+                .collect(),
+        );
+
+        let row = Function::new_unquoted("tuple")
+            .args(
+                table_type
+                    .columns
+                    .keys()
+                    .map(|column_alias| {
+                        Expr::CompoundIdentifier(vec![
+                            Ident::new_quoted("_origin"),
+                            Ident::new_quoted(column_alias.to_string()),
+                        ])
+                        .into_arg()
+                    })
+                    .collect(),
+            )
+            .into_expr();
+
+        let select = vec![Function::new_unquoted("toJSONString")
+            .args(vec![Function::new_unquoted("groupArray")
+                .args(vec![Function::new_unquoted("cast")
+                    .args(vec![
+                        row.into_arg(),
+                        Expr::Value(Value::SingleQuotedString(row_cast_type.to_string()))
+                            .into_arg(),
+                    ])
+                    .into_expr()
+                    .into_arg()])
+                .into_expr()
+                .into_arg()])
+            .into_expr()
+            .into_select(Some("rows"))];
+
+        let from = vec![NativeQuery::new(elements)
+            .into_table_factor()
+            .alias("_origin")
+            .into_table_with_joins(vec![])];
+
+        Ok(Query::new()
+            .select(select)
+            .from(from)
+            .into_statement()
+            .format("TabSeparatedRaw"))
+    }
+    /// Compile the predicate argument, with plain column identifiers
+    fn predicate(
+        &self,
+        procedure: &ClickHouseProcedure,
+        table_alias: &CollectionName,
+        arguments: &BTreeMap<ArgumentName, serde_json::Value>,
+        parameters: &mut ParameterBuilder,
+    ) -> Result<Expr, QueryBuilderError> {
+        let expression: models::Expression = serde_json::from_value(
+            required_argument(procedure, arguments, PREDICATE_ARGUMENT)?.to_owned(),
+        )
+        .map_err(|err| QueryBuilderError::InvalidProcedureArgument {
+            procedure: procedure.name(),
+            argument: PREDICATE_ARGUMENT.into(),
+            message: err.to_string(),
+        })?;
+
+        // the predicate is compiled the same way as a query predicate on this table
+        let request = models::QueryRequest {
+            collection: table_alias.to_owned(),
+            query: models::Query {
+                aggregates: None,
+                fields: None,
+                limit: None,
+                offset: None,
+                order_by: None,
+                predicate: None,
+            },
+            arguments: BTreeMap::new(),
+            collection_relationships: self.collection_relationships.to_owned(),
+            variables: None,
+        };
+
+        QueryBuilder::new(&request, self.configuration).mutation_predicate(&expression, parameters)
+    }
+    /// Bind the new value for each column in the set argument
+    fn assignments(
+        &self,
+        procedure: &ClickHouseProcedure,
+        table: &TableConfig,
+        arguments: &BTreeMap<ArgumentName, serde_json::Value>,
+        parameters: &mut ParameterBuilder,
+    ) -> Result<Vec<Assignment>, QueryBuilderError> {
+        let set = match required_argument(procedure, arguments, UPDATE_SET_ARGUMENT)? {
+            serde_json::Value::Object(set) if !set.is_empty() => set,
+            _ => {
+                return Err(QueryBuilderError::InvalidProcedureArgument {
+                    procedure: procedure.name(),
+                    argument: UPDATE_SET_ARGUMENT.into(),
+                    message: "expected an object with at least one column".to_string(),
+                })
+            }
+        };
+
+        let table_type = self
+            .configuration
+            .table_types
+            .get(&table.return_type)
+            .ok_or_else(|| QueryBuilderError::UnknownTableType(table.return_type.to_owned()))?;
+
+        set.iter()
+            .map(|(column_alias, value)| {
+                let column_alias: FieldName = column_alias.to_owned().into();
+                let column_type = table_type.columns.get(&column_alias).ok_or_else(|| {
+                    QueryBuilderError::UnknownColumn(
+                        column_alias.to_owned(),
+                        table.return_type.to_owned(),
+                    )
+                })?;
+
+                // set fields are nullable in the schema so they may be omitted,
+                // but only nullable columns may be set to null
+                if value.is_null() {
+                    let type_definition = ClickHouseTypeDefinition::from_table_column(
+                        column_type,
+                        &column_alias,
+                        &table.return_type,
+                        &self.configuration.namespace_separator,
+                    );
+
+                    if !matches!(type_definition, ClickHouseTypeDefinition::Nullable { .. }) {
+                        return Err(QueryBuilderError::InvalidProcedureArgument {
+                            procedure: procedure.name(),
+                            argument: UPDATE_SET_ARGUMENT.into(),
+                            message: format!(
+                                "column {column_alias} is not nullable, and cannot be set to null"
+                            ),
+                        });
+                    }
+                }
+
+                Ok(Assignment::new(
+                    Ident::new_quoted(column_alias.to_string()),
+                    parameters.bind_json(value, column_type.to_owned().into())?,
+                ))
+            })
+            .collect()
+    }
+    fn insert_rows(
+        &self,
+        procedure: &ClickHouseProcedure,
+        arguments: &'r BTreeMap<ArgumentName, serde_json::Value>,
+    ) -> Result<&'r Vec<serde_json::Value>, QueryBuilderError> {
+        let rows = required_argument(procedure, arguments, INSERT_ROWS_ARGUMENT)?;
+
+        match rows {
+            serde_json::Value::Array(rows) if !rows.is_empty() => Ok(rows),
+            _ => Err(QueryBuilderError::InvalidProcedureArgument {
+                procedure: procedure.name(),
+                argument: INSERT_ROWS_ARGUMENT.into(),
+                message: "expected a non-empty array of rows".to_string(),
+            }),
+        }
+    }
+}
+
+/// The fully qualified name of a table
+fn table_name(table: &TableConfig) -> ObjectName {
+    ObjectName(vec![
+        Ident::new_quoted(&table.schema),
+        Ident::new_quoted(&table.name),
+    ])
+}
+
+/// The query summary, which insert procedures report written rows from
+fn required_summary(summary: Option<&QuerySummary>) -> Result<&QuerySummary, QueryBuilderError> {
+    summary.ok_or_else(|| {
+        QueryBuilderError::Unexpected("missing X-ClickHouse-Summary response header".to_string())
+    })
+}
+
+/// Error on any argument this procedure does not take
+fn check_arguments(
+    procedure: &ClickHouseProcedure,
+    arguments: &BTreeMap<ArgumentName, serde_json::Value>,
+    expected_arguments: &[&str],
+) -> Result<(), QueryBuilderError> {
+    match arguments
+        .keys()
+        .find(|argument| !expected_arguments.contains(&argument.as_str()))
+    {
+        Some(argument) => Err(QueryBuilderError::UnknownProcedureArgument {
+            procedure: procedure.name(),
+            argument: argument.to_owned(),
+        }),
+        None => Ok(()),
+    }
+}
+
+fn required_argument<'a>(
+    procedure: &ClickHouseProcedure,
+    arguments: &'a BTreeMap<ArgumentName, serde_json::Value>,
+    argument_name: &str,
+) -> Result<&'a serde_json::Value, QueryBuilderError> {
+    arguments
+        .get(argument_name)
+        .ok_or_else(|| QueryBuilderError::MissingProcedureArgument {
+            procedure: procedure.name(),
+            argument: argument_name.into(),
+        })
+}
+
+/// Bind the procedure arguments to the query parameters
+fn native_query_elements(
+    procedure: &ClickHouseProcedure,
+    query: &ParameterizedQueryConfig,
+    arguments: &BTreeMap<ArgumentName, serde_json::Value>,
+    parameters: &mut ParameterBuilder,
+) -> Result<Vec<NativeQueryElement>, QueryBuilderError> {
+    let query_argument = |name: &str| {
+        arguments
+            .get(name)
+            .ok_or_else(|| QueryBuilderError::MissingProcedureArgument {
+                procedure: procedure.name(),
+                argument: name.to_owned().into(),
+            })
+    };
+
+    for argument in arguments.keys() {
+        let is_query_argument = query.query.elements.iter().any(|element| match element {
+            ParameterizedQueryElement::String(_) => false,
+            ParameterizedQueryElement::Parameter(p) => p.name.value() == argument.inner(),
+        });
+
+        if !is_query_argument {
+            return Err(QueryBuilderError::UnknownProcedureArgument {
+                procedure: procedure.name(),
+                argument: argument.to_owned(),
+            });
+        }
+    }
+
+    query
+        .query
+        .elements
+        .iter()
+        .map(|element| match element {
+            ParameterizedQueryElement::String(s) => Ok(NativeQueryElement::String(s.to_owned())),
+            ParameterizedQueryElement::Parameter(p) => Ok(NativeQueryElement::Expr(
+                parameters.bind_json(query_argument(p.name.value())?, p.r#type.to_owned())?,
+            )),
+        })
+        .collect()
+}
+
+/// Apply the requested fields to a procedure result
+fn project_fields(
+    value: serde_json::Value,
+    fields: Option<&models::NestedField>,
+) -> Result<serde_json::Value, QueryBuilderError> {
+    match (fields, value) {
+        (None, value) => Ok(value),
+        (Some(_), serde_json::Value::Null) => Ok(serde_json::Value::Null),
+        (Some(models::NestedField::Object(nested_object)), serde_json::Value::Object(object)) => {
+            let mut projected = serde_json::Map::new();
+
+            for (alias, field) in &nested_object.fields {
+                match field {
+                    models::Field::Column { column, fields, .. } => {
+                        let value = object
+                            .get(column.as_str())
+                            .cloned()
+                            .unwrap_or(serde_json::Value::Null);
+                        projected
+                            .insert(alias.to_string(), project_fields(value, fields.as_ref())?);
+                    }
+                    models::Field::Relationship { .. } => {
+                        return Err(QueryBuilderError::NotSupported(
+                            "Relationships in procedure results".to_string(),
+                        ))
+                    }
+                }
+            }
+
+            Ok(serde_json::Value::Object(projected))
+        }
+        (Some(models::NestedField::Array(nested_array)), serde_json::Value::Array(elements)) => {
+            Ok(serde_json::Value::Array(
+                elements
+                    .into_iter()
+                    .map(|element| project_fields(element, Some(&nested_array.fields)))
+                    .collect::<Result<_, _>>()?,
+            ))
+        }
+        (Some(_), value) => Err(QueryBuilderError::Unexpected(format!(
+            "procedure result {value} does not match the requested fields"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use common::{
+        clickhouse_parser::parameterized_query::ParameterizedQuery,
+        config::{ConnectionConfig, TableType},
+        config_file::ParameterizedQueryExposedAs,
+    };
+    use serde_json::json;
+
+    fn configuration(mutations_sync: Option<MutationsSync>) -> ServerConfig {
+        let table = TableConfig {
+            name: "events".to_string(),
+            schema: "analytics".to_string(),
+            comment: None,
+            primary_key: None,
+            arguments: BTreeMap::new(),
+            view: Some(false),
+            return_type: "events".into(),
+            destructive_procedures: true,
+            mutations_sync,
+            async_insert: None,
+        };
+        let query = |exposed_as| ParameterizedQueryConfig {
+            exposed_as,
+            comment: None,
+            query: ParameterizedQuery {
+                elements: vec![ParameterizedQueryElement::String("SELECT 1".to_string())],
+            },
+            return_type: "events".into(),
+        };
+
+        ServerConfig {
+            connection: ConnectionConfig::default(),
+            namespace_separator: ".".to_string(),
+            table_types: BTreeMap::from([(
+                "events".into(),
+                TableType {
+                    comment: None,
+                    columns: BTreeMap::from([
+                        ("id".into(), ClickHouseDataType::UInt64),
+                        ("name".into(), ClickHouseDataType::String),
+                    ]),
+                },
+            )]),
+            tables: BTreeMap::from([("events".into(), table)]),
+            queries: BTreeMap::from([
+                (
+                    "recent_events".into(),
+                    query(ParameterizedQueryExposedAs::Procedure),
+                ),
+                (
+                    "rollup_events".into(),
+                    query(ParameterizedQueryExposedAs::InsertProcedure),
+                ),
+            ]),
+            aggregate_functions: BTreeMap::new(),
+        }
+    }
+
+    fn operation(operation: serde_json::Value) -> models::MutationOperation {
+        serde_json::from_value(operation).expect("Should be a valid mutation operation")
+    }
+
+    fn procedure_result(
+        configuration: &ServerConfig,
+        operation: &models::MutationOperation,
+        response: &[u8],
+        summary: Option<&QuerySummary>,
+    ) -> Result<serde_json::Value, QueryBuilderError> {
+        let collection_relationships = BTreeMap::new();
+
+        MutationBuilder::new(operation, &collection_relationships, configuration)
+            .procedure_result(response, summary)
+    }
+
+    fn summary(written_rows: u64, written_bytes: u64) -> QuerySummary {
+        QuerySummary {
+            written_rows,
+            written_bytes,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn insert_result_projects_requested_fields() {
+        let configuration = configuration(None);
+        let insert = operation(json!({
+            "type": "procedure",
+            "name": "insert_events",
+            "arguments": {
+                "rows": [
+                    { "id": 1, "name": "page_view" },
+                    { "id": 2 }
+                ]
+            },
+            "fields": {
+                "type": "object",
+                "fields": {
+                    "count": { "type": "column", "column": "affected_rows" },
+                    "submitted_rows": {
+                        "type": "column",
+                        "column": "submitted_rows",
+                        "fields": {
+                            "type": "array",
+                            "fields": {
+                                "type": "object",
+                                "fields": {
+                                    "id": { "type": "column", "column": "id" },
+                                    "name": { "type": "column", "column": "name" }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }));
+
+        let result = procedure_result(&configuration, &insert, b"", Some(&summary(2, 64)))
+            .expect("Should shape insert result");
+
+        // 64 bit integers are strings, fields are aliased, and columns missing from a row are null
+        assert_eq!(
+            result,
+            json!({
+                "count": "2",
+                "submitted_rows": [
+                    { "id": 1, "name": "page_view" },
+                    { "id": 2, "name": null }
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn insert_result_without_fields_returns_every_field() {
+        let configuration = configuration(None);
+        let insert = operation(json!({
+            "type": "procedure",
+            "name": "insert_events",
+            "arguments": { "rows": [{ "id": 1 }] }
+        }));
+
+        let result = procedure_result(&configuration, &insert, b"", Some(&summary(1, 8)))
+            .expect("Should shape insert result");
+
+        assert_eq!(
+            result,
+            json!({
+                "affected_rows": "1",
+                "flushed": true,
+                "submitted_rows": [{ "id": 1 }]
+            })
+        );
+    }
+
+    #[test]
+    fn insert_result_requires_summary() {
+        let configuration = configuration(None);
+        let insert = operation(json!({
+            "type": "procedure",
+            "name": "insert_events",
+            "arguments": { "rows": [{ "id": 1 }] }
+        }));
+
+        let result = procedure_result(&configuration, &insert, b"", None);
+
+        assert_eq!(
+            result,
+            Err(QueryBuilderError::Unexpected(
+                "missing X-ClickHouse-Summary response header".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn delete_and_update_results_report_completion() {
+        let delete = operation(json!({
+            "type": "procedure",
+            "name": "delete_events_where",
+            "arguments": {}
+        }));
+        let update = operation(json!({
+            "type": "procedure",
+            "name": "update_events_where",
+            "arguments": {}
+        }));
+
+        let test_cases = vec![
+            (None, &delete, true),
+            (None, &update, false),
+            (Some(MutationsSync::Async), &update, false),
+            (Some(MutationsSync::Local), &update, true),
+            (Some(MutationsSync::AllReplicas), &update, true),
+        ];
+
+        for (mutations_sync, operation, completed) in test_cases {
+            let configuration = configuration(mutations_sync);
+
+            // the summary is not used, as ClickHouse does not report rows deleted or updated
+            let result = procedure_result(&configuration, operation, b"", None)
+                .expect("Should shape mutation result");
+
+            assert_eq!(
+                result,
+                json!({ "completed": completed }),
+                "mutations_sync {mutations_sync:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn insert_select_result_reports_written_counts() {
+        let configuration = configuration(None);
+        let insert_select = operation(json!({
+            "type": "procedure",
+            "name": "rollup_events",
+            "arguments": {},
+            "fields": {
+                "type": "object",
+                "fields": {
+                    "written_rows": { "type": "column", "column": "written_rows" }
+                }
+            }
+        }));
+
+        let result = procedure_result(&configuration, &insert_select, b"", Some(&summary(3, 96)))
+            .expect("Should shape insert select result");
+
+        assert_eq!(result, json!({ "written_rows": "3" }));
+    }
+
+    #[test]
+    fn native_query_result_parses_rows() {
+        let configuration = configuration(None);
+        let native_query = operation(json!({
+            "type": "procedure",
+            "name": "recent_events",
+            "arguments": {},
+            "fields": {
+                "type": "array",
+                "fields": {
+                    "type": "object",
+                    "fields": {
+                        "name": { "type": "column", "column": "name" }
+                    }
+                }
+            }
+        }));
+
+        let result = procedure_result(
+            &configuration,
+            &native_query,
+            br#"[{"id":"1","name":"page_view"},{"id":"2","name":"sign_up"}]"#,
+            None,
+        )
+        .expect("Should shape native query result");
+
+        assert_eq!(
+            result,
+            json!([{ "name": "page_view" }, { "name": "sign_up" }])
+        );
+    }
+
+    #[test]
+    fn native_query_result_rejects_invalid_response() {
+        let configuration = configuration(None);
+        let native_query = operation(json!({
+            "type": "procedure",
+            "name": "recent_events",
+            "arguments": {}
+        }));
+
+        let result = procedure_result(&configuration, &native_query, b"not json", None);
+
+        assert!(
+            matches!(
+                &result,
+                Err(QueryBuilderError::Unexpected(message))
+                    if message.starts_with("unable to parse native query response")
+            ),
+            "unexpected result {result:?}"
+        );
+    }
+
+    #[test]
+    fn project_fields_rejects_mismatched_shapes() {
+        let fields: models::NestedField = serde_json::from_value(json!({
+            "type": "object",
+            "fields": {
+                "id": { "type": "column", "column": "id" }
+            }
+        }))
+        .expect("Should be valid nested fields");
+
+        assert_eq!(
+            project_fields(json!(null), Some(&fields)),
+            Ok(json!(null)),
+            "null values are returned as is"
+        );
+        assert_eq!(
+            project_fields(json!([{ "id": 1 }]), Some(&fields)),
+            Err(QueryBuilderError::Unexpected(
+                r#"procedure result [{"id":1}] does not match the requested fields"#.to_string()
+            ))
+        );
+    }
+}
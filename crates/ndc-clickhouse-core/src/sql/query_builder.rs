@@ -5,9 +5,12 @@ mod error;
 mod function_expression;
 pub mod parameter;
 mod typecasting;
-use self::{collection_context::CollectionContext, typecasting::RowsetTypeString};
+use self::{
+    collection_context::{CollectionContext, RootCollection},
+    typecasting::{json_path_argument, json_path_type, map_key_argument, RowsetTypeString},
+};
 use super::ast::*;
-use binary_comparison_expression::apply_binary_operator;
+use binary_comparison_expression::{apply_binary_operator, comparison_argument_type};
 use common::{
     clickhouse_parser::{
         datatype::ClickHouseDataType, parameterized_query::ParameterizedQueryElement,
@@ -21,18 +24,23 @@ use common::{
 };
 use comparison_column::ComparisonColumn;
 pub use error::QueryBuilderError;
-use function_expression::apply_function;
+use function_expression::{apply_function, apply_parameterized_function};
 use indexmap::IndexMap;
 use ndc_models::{
     self as models, AggregateFunctionName, ArgumentName, CollectionName, FieldName, ObjectTypeName,
     RelationshipName, VariableName,
 };
 use parameter::ParameterBuilder;
-use std::{collections::BTreeMap, iter, str::FromStr};
+use std::{cell::RefCell, collections::BTreeMap, iter, str::FromStr};
 
 pub struct QueryBuilder<'r, 'c> {
     request: &'r models::QueryRequest,
     configuration: &'c ServerConfig,
+    /// collections with variable arguments referenced by the query being built, each expanded once in a common table expression
+    varset_collections: RefCell<Vec<VarsetCollection>>,
+    /// whether column identifiers are qualified by the alias of their table.
+    /// Mutation statements cannot alias the table, so mutation predicates use plain column identifiers
+    qualify_columns: bool,
 }
 
 type Parameters = Vec<(String, String)>;
@@ -42,6 +50,8 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
         Self {
             request,
             configuration,
+            varset_collections: RefCell::new(vec![]),
+            qualify_columns: true,
         }
     }
     pub fn build_parameterized(&self) -> Result<(Statement, Parameters), QueryBuilderError> {
@@ -58,6 +68,49 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
 
         Ok(statement)
     }
+    /// Compile a predicate against the request collection, for use in mutation statements.
+    /// Mutations cannot alias the table or join other tables, so columns are plain identifiers,
+    /// and predicates that would require joins, such as exists predicates and comparisons across relationships, are not supported.
+    /// Mutations are not executed once per variable set, so predicates referencing variables are not supported either
+    pub(crate) fn mutation_predicate(
+        self,
+        expression: &models::Expression,
+        parameters: &mut ParameterBuilder,
+    ) -> Result<Expr, QueryBuilderError> {
+        if references_variables(expression) {
+            return Err(QueryBuilderError::NotSupported(
+                "Mutation predicates referencing variables".to_string(),
+            ));
+        }
+
+        let builder = Self {
+            qualify_columns: false,
+            ..self
+        };
+        let collection =
+            CollectionContext::new(&builder.request.collection, &builder.request.arguments);
+        let mut name_index = 0;
+
+        // columns are not qualified, so the alias is only used by joins, which are rejected below
+        let table_ident = Ident::new_quoted(builder.request.collection.to_string());
+
+        let (predicate, joins) = builder.filter_expression(
+            expression,
+            &table_ident,
+            &collection,
+            RootCollection::Current,
+            &mut name_index,
+            parameters,
+        )?;
+
+        if !joins.is_empty() {
+            return Err(QueryBuilderError::NotSupported(
+                "Mutation predicates referencing relationships or other collections".to_string(),
+            ));
+        }
+
+        Ok(predicate)
+    }
     fn root_query(
         &self,
         parameters: &mut ParameterBuilder,
@@ -65,6 +118,8 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
         let collection = CollectionContext::new(&self.request.collection, &self.request.arguments);
         let query = &self.request.query;
 
+        self.varset_collections.take();
+
         let select = vec![Function::new_unquoted("toJSONString")
             .args(vec![Function::new_unquoted("groupArray")
                 .args(vec![Function::new_unquoted("cast")
@@ -93,7 +148,7 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
             .into_expr()
             .into_select(Some("rowsets"))];
 
-        let with = if let Some(variables) = &self.request.variables {
+        let mut with = if let Some(variables) = &self.request.variables {
             // we make the following assumptions here:
             // variables is an array with at least one item
             // all items in variables are object with keys and primitive values
@@ -139,10 +194,17 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
         };
 
         let rowset_subquery = self
-            .rowset_subquery(&collection, &vec![], query, parameters)?
+            .rowset_subquery(&collection, &vec![], None, query, parameters)?
             .into_table_factor()
             .alias("_rowset");
 
+        with.extend(
+            self.varset_collections
+                .take()
+                .into_iter()
+                .map(|varset_collection| varset_collection.with_item),
+        );
+
         let from = if self.request.variables.is_some() {
             let table = ObjectName(vec![Ident::new_quoted("_vars")])
                 .into_table_factor()
@@ -183,6 +245,7 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
         &self,
         current_collection: &CollectionContext,
         relkeys: &Vec<&FieldName>,
+        fan_out: Option<RelationshipFanOut>,
         query: &models::Query,
         parameters: &mut ParameterBuilder,
     ) -> Result<Query, QueryBuilderError> {
@@ -251,7 +314,7 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                                         Ident::new_quoted("_row"),
                                         Ident::new_quoted(format!("_agg_{alias}")),
                                     ]);
-                                    apply_function(&aggregate_function(function)?, column)
+                                    aggregate_function(function, column, self.configuration)?
                                 }
                             }
                             .into_arg()
@@ -301,7 +364,7 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
         }
 
         let from = vec![self
-            .row_subquery(current_collection, relkeys, query, parameters)?
+            .row_subquery(current_collection, relkeys, fan_out, query, parameters)?
             .into_table_factor()
             .alias("_row")
             .into_table_with_joins(vec![])];
@@ -312,6 +375,7 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
         &self,
         current_collection: &CollectionContext,
         relkeys: &Vec<&FieldName>,
+        fan_out: Option<RelationshipFanOut>,
         query: &models::Query,
         parameters: &mut ParameterBuilder,
     ) -> Result<Query, QueryBuilderError> {
@@ -324,7 +388,8 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                 relation: self
                     .collection_ident(current_collection, parameters)?
                     .alias("_origin"),
-                join_operator: JoinOperator::CrossJoin,
+                join_operator: self
+                    .vars_join_operator(current_collection, &Ident::new_quoted("_origin"))?,
             }];
             (table, joins)
         } else {
@@ -334,18 +399,87 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
             (table, vec![])
         };
 
+        // relationship keys are usually columns of the current collection,
+        // except when fanning out from an array of keys, where the key is the whole array
+        let fan_out_keys = if let Some(fan_out) = fan_out {
+            base_joins.push(fan_out.join);
+            Some((fan_out.target_column, fan_out.keys))
+        } else {
+            None
+        };
+        let relkey_expr = |relkey: &FieldName| match &fan_out_keys {
+            Some((target_column, keys)) if target_column == relkey => keys.clone(),
+            _ => Expr::CompoundIdentifier(vec![
+                Ident::new_quoted("_origin"),
+                self.column_ident(relkey),
+            ]),
+        };
+
         let mut select = vec![];
 
         if let Some(fields) = &query.fields {
             let mut rel_index = 0;
+            let origin_alias = Ident::new_quoted("_origin");
+            let relationship_source = RelationshipSource {
+                alias: &origin_alias,
+                collection: current_collection,
+                predicate: query.predicate.as_ref(),
+            };
             for (alias, field) in fields {
                 match field {
                     models::Field::Column {
                         column,
                         fields,
-                        arguments: _,
+                        arguments,
                     } => {
                         let data_type = self.column_data_type(column, current_collection)?;
+
+                        if let Some((key_type, _, key)) = map_key_argument(&data_type, arguments) {
+                            let key = match key {
+                                models::Argument::Literal { value } => {
+                                    parameters.bind_json(value, key_type.to_owned().into())?
+                                }
+                                models::Argument::Variable { name } => self.variable_ident(name)?,
+                            };
+                            let expr = Expr::Subscript {
+                                expr: Expr::CompoundIdentifier(vec![
+                                    Ident::new_quoted("_origin"),
+                                    self.column_ident(column),
+                                ])
+                                .into_box(),
+                                subscript: key.into_box(),
+                            };
+                            select.push(expr.into_select(Some(format!("_field_{alias}"))));
+                            continue;
+                        }
+
+                        if let Some(path) = json_path_argument(&data_type, arguments) {
+                            let path = match path {
+                                models::Argument::Literal {
+                                    value: serde_json::Value::String(path),
+                                } => path,
+                                models::Argument::Literal { value } => {
+                                    return Err(QueryBuilderError::UnsupportedParameterCast {
+                                        value: value.to_owned(),
+                                        data_type: ClickHouseDataType::String.into(),
+                                    })
+                                }
+                                models::Argument::Variable { .. } => {
+                                    return Err(QueryBuilderError::NotSupported(
+                                        "variables as JSON path arguments".to_string(),
+                                    ))
+                                }
+                            };
+                            let expr = Expr::CompoundIdentifier(
+                                vec![Ident::new_quoted("_origin"), self.column_ident(column)]
+                                    .into_iter()
+                                    .chain(path.split('.').map(Ident::new_quoted))
+                                    .collect(),
+                            );
+                            select.push(expr.into_select(Some(format!("_field_{alias}"))));
+                            continue;
+                        }
+
                         let return_type =
                             get_return_type(current_collection.alias(), self.configuration)?;
                         let column_definition = ClickHouseTypeDefinition::from_table_column(
@@ -355,12 +489,15 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                             &self.configuration.namespace_separator,
                         );
 
-                        let column_ident =
-                            vec![Ident::new_quoted("_origin"), self.column_ident(column)];
+                        let column_ident = vec![
+                            relationship_source.alias.to_owned(),
+                            self.column_ident(column),
+                        ];
 
                         if let Some((expr, mut joins)) = self.column_accessor(
                             column_ident,
                             &column_definition,
+                            relationship_source,
                             false,
                             fields.as_ref(),
                             &mut rel_index,
@@ -384,7 +521,9 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                         let (expr, join) = self.field_relationship(
                             alias,
                             &mut rel_index,
-                            &[Ident::new_quoted("_origin")],
+                            &[relationship_source.alias.to_owned()],
+                            relationship_source,
+                            None,
                             query,
                             relationship,
                             arguments,
@@ -400,26 +539,26 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
 
         if let Some(aggregates) = &query.aggregates {
             for (alias, aggregate) in aggregates {
-                if let models::Aggregate::ColumnCount { column, .. }
-                | models::Aggregate::SingleColumn { column, .. } = aggregate
+                if let models::Aggregate::ColumnCount {
+                    column, field_path, ..
+                }
+                | models::Aggregate::SingleColumn {
+                    column, field_path, ..
+                } = aggregate
                 {
-                    let expr = Expr::CompoundIdentifier(vec![
+                    let (expr, _) = self.nested_column(
                         Ident::new_quoted("_origin"),
-                        self.column_ident(column),
-                    ]);
+                        column,
+                        field_path,
+                        current_collection,
+                    )?;
                     select.push(expr.into_select(Some(format!("_agg_{alias}"))))
                 }
             }
         }
 
         for relkey in relkeys {
-            select.push(
-                Expr::CompoundIdentifier(vec![
-                    Ident::new_quoted("_origin"),
-                    self.column_ident(relkey),
-                ])
-                .into_select(Some(format!("_relkey_{relkey}"))),
-            )
+            select.push(relkey_expr(relkey).into_select(Some(format!("_relkey_{relkey}"))))
         }
 
         if self.request.variables.is_some() {
@@ -441,7 +580,7 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                 predicate,
                 &Ident::new_quoted("_origin"),
                 current_collection,
-                true,
+                RootCollection::Current,
                 &mut 0,
                 parameters,
             )
@@ -450,7 +589,8 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
             (None, vec![])
         };
 
-        let (order_by_exprs, order_by_joins) = self.order_by(&query.order_by, parameters)?;
+        let (order_by_exprs, order_by_joins) =
+            self.order_by(&query.order_by, current_collection, parameters)?;
 
         let joins = base_joins
             .into_iter()
@@ -460,15 +600,7 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
 
         let from = vec![table.into_table_with_joins(joins)];
 
-        let mut limit_by_cols = relkeys
-            .iter()
-            .map(|relkey| {
-                Ok(Expr::CompoundIdentifier(vec![
-                    Ident::new_quoted("_origin"),
-                    self.column_ident(relkey),
-                ]))
-            })
-            .collect::<Result<Vec<_>, QueryBuilderError>>()?;
+        let mut limit_by_cols: Vec<_> = relkeys.iter().map(|relkey| relkey_expr(relkey)).collect();
 
         if self.request.variables.is_some() {
             limit_by_cols.push(Expr::CompoundIdentifier(vec![
@@ -514,6 +646,7 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
     fn order_by(
         &self,
         order_by: &Option<models::OrderBy>,
+        current_collection: &CollectionContext,
         parameters: &mut ParameterBuilder,
     ) -> Result<(Vec<OrderByExpr>, Vec<Join>), QueryBuilderError> {
         let mut order_by_exprs = vec![];
@@ -527,12 +660,14 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                     models::OrderByTarget::Column {
                         name,
                         path,
-                        field_path: _,
+                        field_path,
                     } if path.is_empty() => {
-                        let expr = Expr::CompoundIdentifier(vec![
+                        let (expr, _) = self.nested_column(
                             Ident::new_quoted("_origin"),
-                            self.column_ident(name),
-                        ]);
+                            name,
+                            field_path,
+                            current_collection,
+                        )?;
                         let asc = match &element.order_direction {
                             models::OrderDirection::Asc => Some(true),
                             models::OrderDirection::Desc => Some(false),
@@ -556,15 +691,11 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                         let relationship =
                             self.collection_relationship(&first_element.relationship)?;
 
-                        if let (
-                            models::RelationshipType::Array,
-                            models::OrderByTarget::Column { .. },
-                        ) = (&relationship.relationship_type, &element.target)
-                        {
-                            return Err(QueryBuilderError::NotSupported(
-                                "order by column across array relationship".to_string(),
-                            ));
-                        }
+                        let mut traversed_array = matches!(
+                            relationship.relationship_type,
+                            models::RelationshipType::Array
+                        );
+
                         let relationship_collection = CollectionContext::from_relationship(
                             relationship,
                             &first_element.arguments,
@@ -623,7 +754,10 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                                         .into_table_factor(),
                                     vec![Join {
                                         relation: table,
-                                        join_operator: JoinOperator::CrossJoin,
+                                        join_operator: self.vars_join_operator(
+                                            &relationship_collection,
+                                            &join_alias,
+                                        )?,
                                     }],
                                 )
                             } else {
@@ -640,7 +774,7 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                                     expression,
                                     &join_alias,
                                     &relationship_collection,
-                                    false,
+                                    RootCollection::Unavailable,
                                     &mut join_index,
                                     parameters,
                                 )?;
@@ -653,6 +787,7 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                             }
 
                             let mut last_join_alias = join_alias;
+                            let mut last_collection_context = relationship_collection;
 
                             for path_element in path.iter().skip(1) {
                                 let join_alias =
@@ -662,14 +797,10 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                                 let relationship =
                                     self.collection_relationship(&path_element.relationship)?;
 
-                                if let (
-                                    models::RelationshipType::Array,
-                                    models::OrderByTarget::Column { .. },
-                                ) = (&relationship.relationship_type, &element.target)
+                                if let models::RelationshipType::Array =
+                                    relationship.relationship_type
                                 {
-                                    return Err(QueryBuilderError::NotSupported(
-                                        "order by column across array relationship".to_string(),
-                                    ));
+                                    traversed_array = true;
                                 }
 
                                 let relationship_collection = CollectionContext::from_relationship(
@@ -677,16 +808,18 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                                     &path_element.arguments,
                                 );
 
-                                let join_exprs = relationship
+                                let mut join_exprs = relationship
                                     .column_mapping
                                     .iter()
                                     .map(|(source_col, target_col)| {
                                         Ok(Expr::BinaryOp {
-                                            left: Expr::CompoundIdentifier(vec![
-                                                last_join_alias.clone(),
-                                                self.column_ident(source_col),
-                                            ])
-                                            .into_box(),
+                                            left: self
+                                                .relationship_join_key(
+                                                    source_col,
+                                                    &last_join_alias,
+                                                    &last_collection_context,
+                                                )?
+                                                .into_box(),
                                             op: BinaryOperator::Eq,
                                             right: Expr::CompoundIdentifier(vec![
                                                 join_alias.clone(),
@@ -697,6 +830,10 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                                     })
                                     .collect::<Result<Vec<_>, QueryBuilderError>>()?;
 
+                                join_exprs.extend(
+                                    self.varset_join_expr(&relationship_collection, &join_alias)?,
+                                );
+
                                 let join_operator = join_exprs
                                     .into_iter()
                                     .reduce(and_reducer)
@@ -720,7 +857,7 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                                         expression,
                                         &join_alias,
                                         &relationship_collection,
-                                        false,
+                                        RootCollection::Unavailable,
                                         &mut join_index,
                                         parameters,
                                     )?;
@@ -733,33 +870,53 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                                 }
 
                                 last_join_alias = join_alias;
+                                last_collection_context = relationship_collection;
                             }
 
                             match &element.target {
                                 models::OrderByTarget::Column {
                                     name,
                                     path: _,
-                                    field_path: _,
+                                    field_path,
                                 } => {
-                                    let column = Expr::CompoundIdentifier(vec![
+                                    let (column, _) = self.nested_column(
                                         last_join_alias,
-                                        self.column_ident(name),
-                                    ]);
-                                    group_by.push(column.clone());
-                                    select.push(column.into_select(Some("_order_by_value")));
+                                        name,
+                                        field_path,
+                                        &last_collection_context,
+                                    )?;
+                                    if traversed_array {
+                                        // multiple related rows may match, so to get a deterministic value we pick
+                                        // the one that would sort first: the minimum when ascending, the maximum when descending
+                                        let function = match &element.order_direction {
+                                            models::OrderDirection::Asc => "min",
+                                            models::OrderDirection::Desc => "max",
+                                        };
+                                        select.push(
+                                            Function::new_unquoted(function)
+                                                .args(vec![column.into_arg()])
+                                                .into_expr()
+                                                .into_select(Some("_order_by_value")),
+                                        );
+                                    } else {
+                                        group_by.push(column.clone());
+                                        select.push(column.into_select(Some("_order_by_value")));
+                                    }
                                 }
                                 models::OrderByTarget::SingleColumnAggregate {
                                     column,
                                     function,
                                     path: _,
-                                    field_path: _,
+                                    field_path,
                                 } => {
-                                    let column = Expr::CompoundIdentifier(vec![
+                                    let (column, _) = self.nested_column(
                                         last_join_alias,
-                                        self.column_ident(column),
-                                    ]);
+                                        column,
+                                        field_path,
+                                        &last_collection_context,
+                                    )?;
                                     select.push(
-                                        apply_function(&aggregate_function(function)?, column)
+                                        aggregate_function(function, column, self.configuration)?
                                             .into_select(Some("_order_by_value")),
                                     );
                                 }
@@ -797,11 +954,13 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                                 .iter()
                                 .map(|(source_col, target_col)| {
                                     Ok(Expr::BinaryOp {
-                                        left: Expr::CompoundIdentifier(vec![
-                                            Ident::new_quoted("_origin"),
-                                            self.column_ident(source_col),
-                                        ])
-                                        .into_box(),
+                                        left: self
+                                            .relationship_join_key(
+                                                source_col,
+                                                &Ident::new_quoted("_origin"),
+                                                current_collection,
+                                            )?
+                                            .into_box(),
                                         op: BinaryOperator::Eq,
                                         right: Expr::CompoundIdentifier(vec![
                                             join_alias.clone(),
@@ -863,12 +1022,17 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
 
         Ok((order_by_exprs, order_by_joins))
     }
+    /// A relationship field, joined to the source collection.
+    /// The source path is rooted at the alias of the source collection, followed by the path to the object
+    /// the relationship is nested in, if any
     #[allow(clippy::too_many_arguments)]
     fn field_relationship(
         &self,
         field_alias: &FieldName,
         name_index: &mut u32,
-        target_path: &[Ident],
+        source_path: &[Ident],
+        source: RelationshipSource,
+        source_object: Option<&ClickHouseTypeDefinition>,
         query: &models::Query,
         relationship: &RelationshipName,
         arguments: &BTreeMap<ArgumentName, models::RelationshipArgument>,
@@ -880,28 +1044,43 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
         let relationship = self.collection_relationship(relationship)?;
         let relationship_collection = CollectionContext::from_relationship(relationship, arguments);
 
-        let mut join_expr = relationship
-            .column_mapping
-            .iter()
-            .map(|(source_col, target_col)| {
-                Ok(Expr::BinaryOp {
-                    left: Expr::CompoundIdentifier(
-                        target_path
-                            .iter()
-                            .cloned()
-                            .chain(iter::once(Ident::new_quoted(source_col.to_string())))
-                            .collect(),
-                    )
-                    .into_box(),
-                    op: BinaryOperator::Eq,
-                    right: Expr::CompoundIdentifier(vec![
-                        Ident::new_quoted(&join_alias),
-                        Ident::new_quoted(format!("_relkey_{target_col}")),
-                    ])
-                    .into_box(),
-                })
+        let mut join_expr = vec![];
+        let mut fan_out = None;
+
+        for (source_col, target_col) in &relationship.column_mapping {
+            let (source_expr, source_type) = self.relationship_source_expr(
+                source_col,
+                source_path,
+                source.collection,
+                source_object,
+            )?;
+
+            if let ClickHouseDataType::Array(_) = source_type {
+                if relationship.column_mapping.len() > 1 {
+                    return Err(QueryBuilderError::NotSupported(
+                        "Relationships from an array of keys with multiple mapped columns"
+                            .to_string(),
+                    ));
+                }
+
+                fan_out = Some(self.relationship_fan_out(
+                    source_expr.clone(),
+                    source,
+                    target_col,
+                    parameters,
+                )?);
+            }
+
+            join_expr.push(Expr::BinaryOp {
+                left: source_expr.into_box(),
+                op: BinaryOperator::Eq,
+                right: Expr::CompoundIdentifier(vec![
+                    Ident::new_quoted(&join_alias),
+                    Ident::new_quoted(format!("_relkey_{target_col}")),
+                ])
+                .into_box(),
             })
-            .collect::<Result<Vec<_>, QueryBuilderError>>()?;
+        }
 
         if self.request.variables.is_some() {
             join_expr.push(Expr::BinaryOp {
@@ -929,7 +1108,13 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
 
         let join = Join {
             relation: self
-                .rowset_subquery(&relationship_collection, &relkeys, query, parameters)?
+                .rowset_subquery(
+                    &relationship_collection,
+                    &relkeys,
+                    fan_out,
+                    query,
+                    parameters,
+                )?
                 .into_table_factor()
                 .alias(&join_alias),
             join_operator,
@@ -942,12 +1127,224 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
 
         Ok((expr, join))
     }
+    /// Resolve a relationship mapping source column, relative to the source collection, or to the object the relationship is nested in.
+    /// Source columns that do not exist are treated as dotted paths to fields nested in named tuples, such as `address.country_id`.
+    /// Returns the column (or object field) the source column is in, the path to the source column within it, and its data type
+    fn relationship_source_column(
+        &self,
+        source_col: &FieldName,
+        source_collection: &CollectionContext,
+        source_object: Option<&ClickHouseTypeDefinition>,
+    ) -> Result<(FieldName, Vec<FieldName>, ClickHouseDataType), QueryBuilderError> {
+        let field_path: Vec<FieldName> = source_col
+            .as_str()
+            .split('.')
+            .map(|field_name| field_name.to_owned().into())
+            .collect();
+
+        match source_object {
+            None => {
+                let return_type = get_return_type(source_collection.alias(), self.configuration)?;
+                let table_columns = &self
+                    .configuration
+                    .table_types
+                    .get(return_type)
+                    .ok_or_else(|| QueryBuilderError::UnknownTableType(return_type.to_owned()))?
+                    .columns;
+
+                if let Some(data_type) = table_columns.get(source_col) {
+                    return Ok((source_col.to_owned(), vec![], data_type.to_owned()));
+                }
+
+                let (column_alias, field_path) = match field_path.split_first() {
+                    Some((column_alias, field_path)) if !field_path.is_empty() => {
+                        (column_alias, field_path)
+                    }
+                    _ => {
+                        return Err(QueryBuilderError::UnknownColumn(
+                            source_col.to_owned(),
+                            return_type.to_owned(),
+                        ))
+                    }
+                };
+
+                let data_type = table_columns.get(column_alias).ok_or_else(|| {
+                    QueryBuilderError::UnknownColumn(source_col.to_owned(), return_type.to_owned())
+                })?;
+                let column_definition = ClickHouseTypeDefinition::from_table_column(
+                    data_type,
+                    column_alias,
+                    return_type,
+                    &self.configuration.namespace_separator,
+                );
+                let type_definition = nested_field_definition(&column_definition, field_path)?;
+
+                Ok((
+                    column_alias.to_owned(),
+                    field_path.to_vec(),
+                    type_definition.cast_type(),
+                ))
+            }
+            Some(object_definition) => {
+                let field_path = match object_definition.non_nullable() {
+                    ClickHouseTypeDefinition::Object { name: _, fields }
+                        if fields.contains_key(source_col) =>
+                    {
+                        vec![source_col.to_owned()]
+                    }
+                    _ => field_path,
+                };
+
+                let type_definition = nested_field_definition(object_definition, &field_path)?;
+
+                let (field_name, field_path) = field_path
+                    .split_first()
+                    .ok_or_else(|| QueryBuilderError::Unexpected("empty field path".to_string()))?;
+
+                Ok((
+                    field_name.to_owned(),
+                    field_path.to_vec(),
+                    type_definition.cast_type(),
+                ))
+            }
+        }
+    }
+    /// An expression accessing a relationship mapping source column, see [`QueryBuilder::relationship_source_column`].
+    /// The source path is the path to the source collection alias, or to the object the relationship is nested in
+    fn relationship_source_expr(
+        &self,
+        source_col: &FieldName,
+        source_path: &[Ident],
+        source_collection: &CollectionContext,
+        source_object: Option<&ClickHouseTypeDefinition>,
+    ) -> Result<(Expr, ClickHouseDataType), QueryBuilderError> {
+        let (column, field_path, data_type) =
+            self.relationship_source_column(source_col, source_collection, source_object)?;
+
+        let column = Expr::CompoundIdentifier(
+            source_path
+                .iter()
+                .cloned()
+                .chain(iter::once(self.column_ident(&column)))
+                .collect(),
+        );
+
+        Ok((tuple_element_access(column, &field_path), data_type))
+    }
+    /// A relationship mapping source column used as a join key, where each source row matches a single key.
+    /// Relationships from an array of keys are only supported in relationship fields, where they fan out
+    fn relationship_join_key(
+        &self,
+        source_col: &FieldName,
+        source_alias: &Ident,
+        source_collection: &CollectionContext,
+    ) -> Result<Expr, QueryBuilderError> {
+        let (expr, data_type) = self.relationship_source_expr(
+            source_col,
+            &[source_alias.to_owned()],
+            source_collection,
+            None,
+        )?;
+
+        if let ClickHouseDataType::Array(_) = data_type {
+            return Err(QueryBuilderError::NotSupported(
+                "Relationships from an array of keys in predicates and order by".to_string(),
+            ));
+        }
+
+        Ok(expr)
+    }
+    /// Relationships from an array of keys fan out to every target row matching any of the keys.
+    /// The target rows are joined to the distinct key arrays of the source collection,
+    /// so they can be grouped by the whole array, which is then used as the relationship key.
+    /// The predicate of the source query is pushed down, so only key arrays of matching source rows are joined.
+    /// The source collection is selected under its own alias, so the keys are accessed through the full source path
+    fn relationship_fan_out(
+        &self,
+        keys: Expr,
+        source: RelationshipSource,
+        target_col: &FieldName,
+        parameters: &mut ParameterBuilder,
+    ) -> Result<RelationshipFanOut, QueryBuilderError> {
+        let fan_out_alias = Ident::new_quoted("_fan_out");
+
+        let select = vec![
+            keys.clone().into_select(Some("_keys")),
+            Function::new_unquoted("arrayJoin")
+                .args(vec![keys.into_arg()])
+                .into_expr()
+                .into_select(Some("_key")),
+        ];
+
+        let (table, mut joins) = if self.request.variables.is_some() {
+            let table = ObjectName(vec![Ident::new_quoted("_vars")])
+                .into_table_factor()
+                .alias("_vars");
+
+            let joins = vec![Join {
+                relation: self
+                    .collection_ident(source.collection, parameters)?
+                    .alias(source.alias),
+                join_operator: self.vars_join_operator(source.collection, source.alias)?,
+            }];
+            (table, joins)
+        } else {
+            let table = self
+                .collection_ident(source.collection, parameters)?
+                .alias(source.alias);
+            (table, vec![])
+        };
+
+        let predicate = if let Some(predicate) = source.predicate {
+            let (predicate, predicate_joins) = self.filter_expression(
+                predicate,
+                source.alias,
+                source.collection,
+                RootCollection::Current,
+                &mut 0,
+                parameters,
+            )?;
+            joins.extend(predicate_joins);
+            Some(predicate)
+        } else {
+            None
+        };
+
+        let subquery = Query::new()
+            .distinct(true)
+            .select(select)
+            .from(vec![table.into_table_with_joins(joins)])
+            .predicate(predicate);
+
+        let join = Join {
+            relation: subquery.into_table_factor().alias(&fan_out_alias),
+            join_operator: JoinOperator::Inner(JoinConstraint::On(Expr::BinaryOp {
+                left: Expr::CompoundIdentifier(vec![
+                    fan_out_alias.clone(),
+                    Ident::new_quoted("_key"),
+                ])
+                .into_box(),
+                op: BinaryOperator::Eq,
+                right: Expr::CompoundIdentifier(vec![
+                    Ident::new_quoted("_origin"),
+                    self.column_ident(target_col),
+                ])
+                .into_box(),
+            })),
+        };
+
+        Ok(RelationshipFanOut {
+            join,
+            target_column: target_col.to_owned(),
+            keys: Expr::CompoundIdentifier(vec![fan_out_alias, Ident::new_quoted("_keys")]),
+        })
+    }
     fn filter_expression(
         &self,
         expression: &models::Expression,
         current_join_alias: &Ident,
         current_collection: &CollectionContext,
-        current_is_origin: bool,
+        root: RootCollection,
         name_index: &mut u32,
         parameters: &mut ParameterBuilder,
     ) -> Result<(Expr, Vec<Join>), QueryBuilderError> {
@@ -960,7 +1357,7 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                             expression,
                             current_join_alias,
                             current_collection,
-                            current_is_origin,
+                            root,
                             name_index,
                             parameters,
                         )
@@ -992,7 +1389,7 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                             expression,
                             current_join_alias,
                             current_collection,
-                            current_is_origin,
+                            root,
                             name_index,
                             parameters,
                         )
@@ -1021,7 +1418,7 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                     expression,
                     current_join_alias,
                     current_collection,
-                    current_is_origin,
+                    root,
                     name_index,
                     parameters,
                 )?;
@@ -1033,7 +1430,7 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                     column,
                     current_join_alias,
                     current_collection,
-                    current_is_origin,
+                    root,
                     name_index,
                     parameters,
                 )?;
@@ -1065,27 +1462,19 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                     column,
                     current_join_alias,
                     current_collection,
-                    current_is_origin,
+                    root,
                     name_index,
                     parameters,
                 )?;
 
-                // special case: right hand data types is assumed to always be the same type as left hand,
-                // except when the operator is IN/NOT IN, where the type is Array(<left hand data type>)
-                let right_col_type = match operator {
-                    ClickHouseBinaryComparisonOperator::In
-                    | ClickHouseBinaryComparisonOperator::NotIn => {
-                        ClickHouseDataType::Array(Box::new(left_col.data_type()))
-                    }
-                    _ => left_col.data_type(),
-                };
+                let right_col_type = comparison_argument_type(&operator, left_col.data_type());
 
                 let right_col = match value {
                     models::ComparisonValue::Column { column } => self.comparison_column(
                         column,
                         current_join_alias,
                         current_collection,
-                        current_is_origin,
+                        root,
                         name_index,
                         parameters,
                     )?,
@@ -1095,10 +1484,7 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                     ),
 
                     models::ComparisonValue::Variable { name } => {
-                        let column_ident = Expr::CompoundIdentifier(vec![
-                            Ident::new_quoted("_vars"),
-                            Ident::new_quoted(format!("_var_{name}")),
-                        ]);
+                        let column_ident = self.variable_ident(name)?;
                         let column_ident = if is_uuid(&right_col_type) {
                             Function::new_unquoted("toUUID")
                                 .args(vec![column_ident.into_arg()])
@@ -1122,92 +1508,468 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
             models::Expression::Exists {
                 in_collection,
                 predicate,
-            } => self.filter_exists_expression(
-                in_collection,
-                predicate,
-                current_join_alias,
-                name_index,
-                parameters,
-            ),
-        }
-    }
-    fn filter_exists_expression(
-        &self,
-        in_collection: &models::ExistsInCollection,
-        expression: &Option<Box<models::Expression>>,
-        previous_join_alias: &Ident,
-        name_index: &mut u32,
-        parameters: &mut ParameterBuilder,
-    ) -> Result<(Expr, Vec<Join>), QueryBuilderError> {
-        let exists_join_ident = Ident::new_quoted(format!("_exists_{}", name_index));
-        *name_index += 1;
-
-        let join_subquery = {
-            let target_collection = match in_collection {
-                models::ExistsInCollection::Related {
-                    relationship,
-                    arguments,
-                } => {
-                    let relationship = self.collection_relationship(relationship)?;
-                    CollectionContext::from_relationship(relationship, arguments)
-                }
-                models::ExistsInCollection::Unrelated {
-                    collection,
-                    arguments,
-                } => CollectionContext::new_unrelated(collection, arguments),
+            } => match in_collection {
                 models::ExistsInCollection::NestedCollection {
-                    column_name: _,
+                    column_name,
                     arguments: _,
-                    field_path: _,
+                    field_path,
                 } => {
-                    return Err(QueryBuilderError::NotSupported(
-                        "Nested Collection exists".to_string(),
-                    ))
-                }
-            };
+                    let data_type = self.column_data_type(column_name, current_collection)?;
+                    let return_type =
+                        get_return_type(current_collection.alias(), self.configuration)?;
+                    let column_definition = ClickHouseTypeDefinition::from_table_column(
+                        &data_type,
+                        column_name,
+                        return_type,
+                        &self.configuration.namespace_separator,
+                    );
+                    let type_definition = nested_field_definition(&column_definition, field_path)?;
 
-            let subquery_origin_alias = Ident::new_quoted(format!("_exists_{}", name_index));
-            *name_index += 1;
+                    let column = tuple_element_access(
+                        Expr::CompoundIdentifier(
+                            self.qualified_column_ident(current_join_alias, column_name),
+                        ),
+                        field_path,
+                    );
 
-            let (predicate, predicate_joins) = match expression {
-                Some(expression) => {
-                    let (predicate, predicate_joins) = self.filter_expression(
-                        expression,
-                        &subquery_origin_alias,
-                        &target_collection,
-                        false,
+                    let expr = self.filter_nested_collection_expression(
+                        predicate,
+                        column,
+                        type_definition,
                         name_index,
                         parameters,
                     )?;
-                    (Some(predicate), predicate_joins)
-                }
-                None => (None, vec![]),
-            };
-
-            let table = self
-                .collection_ident(&target_collection, parameters)?
-                .alias(&subquery_origin_alias);
 
-            let (table, base_joins) = if self.request.variables.is_some() {
-                (
-                    ObjectName(vec![Ident::new_quoted("_vars")]).into_table_factor(),
-                    vec![Join {
-                        relation: table,
-                        join_operator: JoinOperator::CrossJoin,
-                    }],
-                )
-            } else {
-                (table, vec![])
-            };
+                    Ok((expr, vec![]))
+                }
+                models::ExistsInCollection::Related { .. }
+                | models::ExistsInCollection::Unrelated { .. } => self.filter_exists_expression(
+                    in_collection,
+                    predicate,
+                    ExistsSource {
+                        join_alias: current_join_alias,
+                        collection: current_collection,
+                        root,
+                    },
+                    name_index,
+                    parameters,
+                ),
+            },
+        }
+    }
+    /// Compile an exists predicate over a nested collection, that is an array of objects (named tuples),
+    /// into `arrayExists(x -> <predicate on x>, <array column>)`.
+    /// The predicate is evaluated against each array element, so it may only reference fields of that element.
+    fn filter_nested_collection_expression(
+        &self,
+        expression: &Option<Box<models::Expression>>,
+        column: Expr,
+        type_definition: &ClickHouseTypeDefinition,
+        name_index: &mut u32,
+        parameters: &mut ParameterBuilder,
+    ) -> Result<Expr, QueryBuilderError> {
+        let element_type = match type_definition.non_nullable() {
+            ClickHouseTypeDefinition::Array { element_type } => &**element_type,
+            _ => {
+                return Err(QueryBuilderError::ColumnTypeMismatch {
+                    expected: "Array".to_string(),
+                    got: type_definition.cast_type().to_string(),
+                })
+            }
+        };
 
-            let joins = base_joins.into_iter().chain(predicate_joins).collect();
+        let element_ident = Ident::new_unquoted(format!("_element_{name_index}"));
+        *name_index += 1;
 
-            let from = vec![table.into_table_with_joins(joins)];
+        let predicate = match expression {
+            Some(expression) => self.nested_collection_element_expression(
+                expression,
+                &element_ident,
+                element_type,
+                name_index,
+                parameters,
+            )?,
+            None => Expr::Value(Value::Boolean(true)),
+        };
 
-            let mut select =
+        Ok(Function::new_unquoted("arrayExists")
+            .args(vec![
+                Lambda::new(vec![element_ident], predicate)
+                    .into_expr()
+                    .into_arg(),
+                column.into_arg(),
+            ])
+            .into_expr())
+    }
+    fn nested_collection_element_expression(
+        &self,
+        expression: &models::Expression,
+        element_ident: &Ident,
+        element_type: &ClickHouseTypeDefinition,
+        name_index: &mut u32,
+        parameters: &mut ParameterBuilder,
+    ) -> Result<Expr, QueryBuilderError> {
+        match expression {
+            models::Expression::And { expressions } => {
+                let and_expression = expressions
+                    .iter()
+                    .map(|expression| {
+                        self.nested_collection_element_expression(
+                            expression,
+                            element_ident,
+                            element_type,
+                            name_index,
+                            parameters,
+                        )
+                    })
+                    .collect::<Result<Vec<_>, _>>()?
+                    .into_iter()
+                    .reduce(and_reducer)
+                    .unwrap_or_else(|| Expr::Value(Value::Boolean(true)));
+
+                Ok(if expressions.len() > 1 {
+                    and_expression.into_nested()
+                } else {
+                    and_expression
+                })
+            }
+            models::Expression::Or { expressions } => {
+                let or_expression = expressions
+                    .iter()
+                    .map(|expression| {
+                        self.nested_collection_element_expression(
+                            expression,
+                            element_ident,
+                            element_type,
+                            name_index,
+                            parameters,
+                        )
+                    })
+                    .collect::<Result<Vec<_>, _>>()?
+                    .into_iter()
+                    .reduce(or_reducer)
+                    .unwrap_or_else(|| Expr::Value(Value::Boolean(false)));
+
+                Ok(if expressions.len() > 1 {
+                    or_expression.into_nested()
+                } else {
+                    or_expression
+                })
+            }
+            models::Expression::Not { expression } => {
+                let expression = self.nested_collection_element_expression(
+                    expression,
+                    element_ident,
+                    element_type,
+                    name_index,
+                    parameters,
+                )?;
+                Ok(Expr::Not(expression.into_nested().into_box()))
+            }
+            models::Expression::UnaryComparisonOperator { column, operator } => {
+                let (field, _) =
+                    nested_collection_element_field(element_ident, element_type, column)?;
+
+                Ok(match operator {
+                    models::UnaryComparisonOperator::IsNull => Expr::BinaryOp {
+                        left: field.into_nested().into_box(),
+                        op: BinaryOperator::Is,
+                        right: Value::Null.into_expr().into_box(),
+                    },
+                })
+            }
+            models::Expression::BinaryComparisonOperator {
+                column,
+                operator,
+                value,
+            } => {
+                let operator = ClickHouseBinaryComparisonOperator::from_str(operator.inner())
+                    .map_err(|_err| {
+                        QueryBuilderError::UnknownBinaryComparisonOperator(operator.to_owned())
+                    })?;
+
+                let (left_col, left_type) =
+                    nested_collection_element_field(element_ident, element_type, column)?;
+                let left_type = left_type.cast_type();
+
+                let right_col_type = comparison_argument_type(&operator, left_type);
+
+                let right_col = match value {
+                    models::ComparisonValue::Column { column } => {
+                        let (right_col, _) =
+                            nested_collection_element_field(element_ident, element_type, column)?;
+                        right_col
+                    }
+                    models::ComparisonValue::Scalar { value } => {
+                        parameters.bind_json(value, right_col_type.into())?
+                    }
+                    models::ComparisonValue::Variable { name } => {
+                        let column_ident = self.variable_ident(name)?;
+                        if is_uuid(&right_col_type) {
+                            Function::new_unquoted("toUUID")
+                                .args(vec![column_ident.into_arg()])
+                                .into_expr()
+                        } else {
+                            column_ident
+                        }
+                    }
+                };
+
+                Ok(apply_binary_operator(&operator, left_col, right_col))
+            }
+            models::Expression::Exists {
+                in_collection,
+                predicate,
+            } => match in_collection {
+                models::ExistsInCollection::NestedCollection {
+                    column_name,
+                    arguments: _,
+                    field_path,
+                } => {
+                    let field_path: Vec<_> = iter::once(column_name.to_owned())
+                        .chain(field_path.iter().cloned())
+                        .collect();
+                    let type_definition = nested_field_definition(element_type, &field_path)?;
+
+                    let column =
+                        tuple_element_access(element_ident.clone().into_expr(), &field_path);
+
+                    self.filter_nested_collection_expression(
+                        predicate,
+                        column,
+                        type_definition,
+                        name_index,
+                        parameters,
+                    )
+                }
+                models::ExistsInCollection::Related { .. }
+                | models::ExistsInCollection::Unrelated { .. } => {
+                    Err(QueryBuilderError::NotSupported(
+                        "Relationships in nested collection predicates".to_string(),
+                    ))
+                }
+            },
+        }
+    }
+    fn filter_exists_expression(
+        &self,
+        in_collection: &models::ExistsInCollection,
+        expression: &Option<Box<models::Expression>>,
+        source: ExistsSource,
+        name_index: &mut u32,
+        parameters: &mut ParameterBuilder,
+    ) -> Result<(Expr, Vec<Join>), QueryBuilderError> {
+        let ExistsSource {
+            join_alias: previous_join_alias,
+            collection: previous_collection,
+            root,
+        } = source;
+        let exists_join_ident = Ident::new_quoted(format!("_exists_{}", name_index));
+        *name_index += 1;
+
+        // subqueries cannot reference the outer query, so if the predicate references root collection columns,
+        // the distinct values of those columns are joined inside the subquery, and become additional join keys.
+        // root_join_alias is the alias of the root collection outside the subquery
+        let mut root_columns = vec![];
+        if let Some(expression) = expression {
+            root_collection_columns(expression, &mut root_columns);
+        }
+
+        let (root_join_alias, root_collection) = if root_columns.is_empty() {
+            (None, None)
+        } else {
+            match root {
+                RootCollection::Current => (Some(previous_join_alias), Some(previous_collection)),
+                RootCollection::Joined { alias, collection } => (Some(alias), Some(collection)),
+                RootCollection::Unavailable => {
+                    return Err(QueryBuilderError::NotSupported(
+                        "Comparisons to root".to_string(),
+                    ))
+                }
+            }
+        };
+
+        let join_subquery = {
+            let target_collection = match in_collection {
+                models::ExistsInCollection::Related {
+                    relationship,
+                    arguments,
+                } => {
+                    let relationship = self.collection_relationship(relationship)?;
+                    CollectionContext::from_relationship(relationship, arguments)
+                }
+                models::ExistsInCollection::Unrelated {
+                    collection,
+                    arguments,
+                } => CollectionContext::new_unrelated(collection, arguments),
+                models::ExistsInCollection::NestedCollection { .. } => {
+                    return Err(QueryBuilderError::Unexpected(
+                        "nested collection exists should not require a subquery".to_string(),
+                    ))
+                }
+            };
+
+            let subquery_origin_alias = Ident::new_quoted(format!("_exists_{}", name_index));
+            *name_index += 1;
+
+            let mut root_joins = vec![];
+
+            let subquery_root_alias = if let Some(root_collection) = root_collection {
+                let subquery_root_alias = Ident::new_quoted(format!("_exists_{}", name_index));
+                *name_index += 1;
+
+                let mut root_key_columns = root_columns.clone();
+
+                // when the exists is directly on the root collection, join only the related root keys
+                let mut join_exprs = match (in_collection, root) {
+                    (
+                        models::ExistsInCollection::Related {
+                            relationship,
+                            arguments: _,
+                        },
+                        RootCollection::Current,
+                    ) => {
+                        let relationship = self.collection_relationship(relationship)?;
+                        relationship
+                            .column_mapping
+                            .iter()
+                            .map(|(source_col, target_col)| {
+                                // source columns nested in tuples are keyed by the whole tuple column
+                                let (source_column, _, _) = self.relationship_source_column(
+                                    source_col,
+                                    root_collection,
+                                    None,
+                                )?;
+                                if !root_key_columns.contains(&source_column) {
+                                    root_key_columns.push(source_column);
+                                }
+                                Ok(Expr::BinaryOp {
+                                    left: self
+                                        .relationship_join_key(
+                                            source_col,
+                                            &subquery_root_alias,
+                                            root_collection,
+                                        )?
+                                        .into_box(),
+                                    op: BinaryOperator::Eq,
+                                    right: Expr::CompoundIdentifier(vec![
+                                        subquery_origin_alias.clone(),
+                                        self.column_ident(target_col),
+                                    ])
+                                    .into_box(),
+                                })
+                            })
+                            .collect::<Result<_, QueryBuilderError>>()?
+                    }
+                    _ => vec![],
+                };
+
+                let varset_join_expr =
+                    self.varset_join_expr(root_collection, &subquery_root_alias)?;
+
+                // the root collection may have many rows per key, so only distinct keys are joined
+                let mut root_keys_select: Vec<_> = root_key_columns
+                    .into_iter()
+                    .map(|column| SelectItem::unnamed(self.column_ident(&column).into_expr()))
+                    .collect();
+
+                if varset_join_expr.is_some() {
+                    root_keys_select.push(SelectItem::unnamed(
+                        self.varset_id_column(root_collection)?.into_expr(),
+                    ));
+                }
+
+                join_exprs.extend(varset_join_expr);
+
+                let join_operator = join_exprs
+                    .into_iter()
+                    .reduce(and_reducer)
+                    .map(JoinConstraint::On)
+                    .map(JoinOperator::Inner)
+                    .unwrap_or(JoinOperator::CrossJoin);
+
+                root_joins.push(Join {
+                    relation: Query::new()
+                        .distinct(true)
+                        .select(root_keys_select)
+                        .from(vec![self
+                            .collection_ident(root_collection, parameters)?
+                            .into_table_with_joins(vec![])])
+                        .into_table_factor()
+                        .alias(&subquery_root_alias),
+                    join_operator,
+                });
+
+                Some(subquery_root_alias)
+            } else {
+                None
+            };
+
+            let subquery_root = match (&subquery_root_alias, root_collection) {
+                (Some(alias), Some(collection)) => RootCollection::Joined { alias, collection },
+                _ => RootCollection::Unavailable,
+            };
+
+            let (predicate, predicate_joins) = match expression {
+                Some(expression) => {
+                    let (predicate, predicate_joins) = self.filter_expression(
+                        expression,
+                        &subquery_origin_alias,
+                        &target_collection,
+                        subquery_root,
+                        name_index,
+                        parameters,
+                    )?;
+                    (Some(predicate), predicate_joins)
+                }
+                None => (None, vec![]),
+            };
+
+            let table = self
+                .collection_ident(&target_collection, parameters)?
+                .alias(&subquery_origin_alias);
+
+            let (table, base_joins) = if self.request.variables.is_some() {
+                (
+                    ObjectName(vec![Ident::new_quoted("_vars")]).into_table_factor(),
+                    vec![Join {
+                        relation: table,
+                        join_operator: self
+                            .vars_join_operator(&target_collection, &subquery_origin_alias)?,
+                    }],
+                )
+            } else {
+                (table, vec![])
+            };
+
+            let joins = base_joins
+                .into_iter()
+                .chain(root_joins)
+                .chain(predicate_joins)
+                .collect();
+
+            let from = vec![table.into_table_with_joins(joins)];
+
+            let mut select =
                 vec![Expr::Value(Value::Boolean(true)).into_select(Some(&exists_join_ident))];
             let mut limit_by = vec![];
 
+            if let Some(subquery_root_alias) = &subquery_root_alias {
+                for root_col in &root_columns {
+                    select.push(
+                        Expr::CompoundIdentifier(vec![
+                            subquery_root_alias.clone(),
+                            self.column_ident(root_col),
+                        ])
+                        .into_select(Some(format!("_rootkey_{root_col}"))),
+                    );
+                    limit_by.push(Expr::CompoundIdentifier(vec![
+                        subquery_root_alias.clone(),
+                        self.column_ident(root_col),
+                    ]));
+                }
+            }
+
             if let models::ExistsInCollection::Related {
                 relationship,
                 arguments: _,
@@ -1270,11 +2032,13 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                     .column_mapping
                     .iter()
                     .map(|(source_col, target_col)| {
-                        let left = Expr::CompoundIdentifier(vec![
-                            previous_join_alias.clone(),
-                            self.column_ident(source_col),
-                        ])
-                        .into_box();
+                        let left = self
+                            .relationship_join_key(
+                                source_col,
+                                previous_join_alias,
+                                previous_collection,
+                            )?
+                            .into_box();
                         let right = Expr::CompoundIdentifier(vec![
                             exists_join_ident.clone(),
                             Ident::new_quoted(format!("_relkey_{target_col}")),
@@ -1292,17 +2056,32 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                 collection: _,
                 arguments: _,
             } => vec![],
-            models::ExistsInCollection::NestedCollection {
-                column_name: _,
-                arguments: _,
-                field_path: _,
-            } => {
-                return Err(QueryBuilderError::NotSupported(
-                    "Nested Collection exists".to_string(),
+            models::ExistsInCollection::NestedCollection { .. } => {
+                return Err(QueryBuilderError::Unexpected(
+                    "nested collection exists should not require a subquery".to_string(),
                 ))
             }
         };
 
+        if let Some(root_join_alias) = root_join_alias {
+            for root_col in &root_columns {
+                let left = Expr::CompoundIdentifier(vec![
+                    root_join_alias.clone(),
+                    self.column_ident(root_col),
+                ]);
+                let right = Expr::CompoundIdentifier(vec![
+                    exists_join_ident.clone(),
+                    Ident::new_quoted(format!("_rootkey_{root_col}")),
+                ]);
+                // root columns may be NULL, and the predicate may still hold for those rows
+                join_exprs.push(
+                    Function::new_unquoted("isNotDistinctFrom")
+                        .args(vec![left.into_arg(), right.into_arg()])
+                        .into_expr(),
+                )
+            }
+        }
+
         if self.request.variables.is_some() {
             let left = Expr::CompoundIdentifier(vec![
                 Ident::new_quoted("_vars"),
@@ -1350,7 +2129,7 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
         column: &models::ComparisonTarget,
         current_join_alias: &Ident,
         current_collection: &CollectionContext,
-        current_is_origin: bool,
+        root: RootCollection,
         name_index: &mut u32,
         parameters: &mut ParameterBuilder,
     ) -> Result<ComparisonColumn, QueryBuilderError> {
@@ -1358,11 +2137,11 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
             models::ComparisonTarget::Column {
                 name: comparison_column_name,
                 path,
-                field_path: _,
+                field_path,
             } => {
                 if let Some(first_element) = path.first() {
-                    if current_is_origin {
-                        let (join, join_alias, last_collection_context) = {
+                    if root.is_current() {
+                        let (join, join_alias, data_type) = {
                             let previous_join_alias = current_join_alias.clone();
                             let current_join_alias =
                                 Ident::new_quoted(format!("_exists_{name_index}"));
@@ -1375,7 +2154,7 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                                 &first_element.arguments,
                             );
 
-                            let (subquery, last_collection_name) = {
+                            let (subquery, data_type) = {
                                 let mut select = vec![];
                                 let mut group_by = vec![];
 
@@ -1419,7 +2198,10 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                                             .into_table_factor(),
                                         vec![Join {
                                             relation: table,
-                                            join_operator: JoinOperator::CrossJoin,
+                                            join_operator: self.vars_join_operator(
+                                                &relationship_collection,
+                                                &join_alias,
+                                            )?,
                                         }],
                                     )
                                 } else {
@@ -1436,7 +2218,7 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                                         expression,
                                         &join_alias,
                                         &relationship_collection,
-                                        false,
+                                        RootCollection::Unavailable,
                                         &mut join_index,
                                         parameters,
                                     )?;
@@ -1464,16 +2246,18 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                                             &path_element.arguments,
                                         );
 
-                                    let join_exprs = relationship
+                                    let mut join_exprs = relationship
                                         .column_mapping
                                         .iter()
                                         .map(|(source_col, target_col)| {
                                             Ok(Expr::BinaryOp {
-                                                left: Expr::CompoundIdentifier(vec![
-                                                    last_join_alias.clone(),
-                                                    self.column_ident(source_col),
-                                                ])
-                                                .into_box(),
+                                                left: self
+                                                    .relationship_join_key(
+                                                        source_col,
+                                                        &last_join_alias,
+                                                        &last_collection_context,
+                                                    )?
+                                                    .into_box(),
                                                 op: BinaryOperator::Eq,
                                                 right: Expr::CompoundIdentifier(vec![
                                                     join_alias.clone(),
@@ -1484,6 +2268,13 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                                         })
                                         .collect::<Result<Vec<_>, QueryBuilderError>>()?;
 
+                                    join_exprs.extend(
+                                        self.varset_join_expr(
+                                            &relationship_collection,
+                                            &join_alias,
+                                        )?,
+                                    );
+
                                     let join_operator = join_exprs
                                         .into_iter()
                                         .reduce(and_reducer)
@@ -1507,7 +2298,7 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                                             expression,
                                             &join_alias,
                                             &relationship_collection,
-                                            false,
+                                            RootCollection::Unavailable,
                                             &mut join_index,
                                             parameters,
                                         )?;
@@ -1523,13 +2314,16 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                                     last_collection_context = relationship_collection;
                                 }
 
+                                let (column, data_type) = self.nested_column(
+                                    last_join_alias,
+                                    comparison_column_name,
+                                    field_path,
+                                    &last_collection_context,
+                                )?;
+
                                 select.push(
                                     Function::new_unquoted("groupArray")
-                                        .args(vec![Expr::CompoundIdentifier(vec![
-                                            last_join_alias,
-                                            self.column_ident(comparison_column_name),
-                                        ])
-                                        .into_arg()])
+                                        .args(vec![column.into_arg()])
                                         .into_expr()
                                         .into_select(Some("_values")),
                                 );
@@ -1548,7 +2342,7 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                                         .from(from)
                                         .predicate(predicate)
                                         .group_by(group_by),
-                                    last_collection_context,
+                                    data_type,
                                 )
                             };
 
@@ -1557,11 +2351,13 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                                 .iter()
                                 .map(|(source_col, target_col)| {
                                     Ok(Expr::BinaryOp {
-                                        left: Expr::CompoundIdentifier(vec![
-                                            previous_join_alias.clone(),
-                                            self.column_ident(source_col),
-                                        ])
-                                        .into_box(),
+                                        left: self
+                                            .relationship_join_key(
+                                                source_col,
+                                                &previous_join_alias,
+                                                current_collection,
+                                            )?
+                                            .into_box(),
                                         op: BinaryOperator::Eq,
                                         right: Expr::CompoundIdentifier(vec![
                                             current_join_alias.clone(),
@@ -1601,7 +2397,7 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                                     .alias(current_join_alias.clone()),
                                 join_operator,
                             };
-                            (join, current_join_alias, last_collection_name)
+                            (join, current_join_alias, data_type)
                         };
 
                         let column_ident = Ident::new_unquoted(format!("_value_{name_index}"));
@@ -1616,10 +2412,7 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                             column_ident,
                             join,
                             values_ident,
-                            self.column_data_type(
-                                comparison_column_name,
-                                &last_collection_context,
-                            )?,
+                            data_type,
                         ))
                     } else {
                         let mut additional_joins = vec![];
@@ -1640,16 +2433,18 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                                 &path_element.arguments,
                             );
 
-                            let join_exprs = relationship
+                            let mut join_exprs = relationship
                                 .column_mapping
                                 .iter()
                                 .map(|(source_col, target_col)| {
                                     Ok(Expr::BinaryOp {
-                                        left: Expr::CompoundIdentifier(vec![
-                                            last_join_alias.clone(),
-                                            self.column_ident(source_col),
-                                        ])
-                                        .into_box(),
+                                        left: self
+                                            .relationship_join_key(
+                                                source_col,
+                                                &last_join_alias,
+                                                &last_collection_context,
+                                            )?
+                                            .into_box(),
                                         op: BinaryOperator::Eq,
                                         right: Expr::CompoundIdentifier(vec![
                                             join_alias.clone(),
@@ -1660,6 +2455,10 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                                 })
                                 .collect::<Result<Vec<_>, QueryBuilderError>>()?;
 
+                            join_exprs.extend(
+                                self.varset_join_expr(&relationship_collection, &join_alias)?,
+                            );
+
                             let join_operator = join_exprs
                                 .into_iter()
                                 .reduce(and_reducer)
@@ -1683,7 +2482,7 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                                     expression,
                                     &join_alias,
                                     &relationship_collection,
-                                    false,
+                                    root,
                                     name_index,
                                     parameters,
                                 )?;
@@ -1699,51 +2498,49 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                             last_collection_context = relationship_collection;
                         }
 
-                        let column_ident = Expr::CompoundIdentifier(vec![
+                        let (column_ident, data_type) = self.nested_column(
                             last_join_alias,
-                            self.column_ident(comparison_column_name),
-                        ]);
+                            comparison_column_name,
+                            field_path,
+                            &last_collection_context,
+                        )?;
 
                         Ok(ComparisonColumn::new_flat(
                             column_ident,
                             additional_joins,
                             additional_predicates.into_iter().reduce(and_reducer),
-                            self.column_data_type(
-                                comparison_column_name,
-                                &last_collection_context,
-                            )?,
+                            data_type,
                         ))
                     }
                 } else {
-                    let column_ident = Expr::CompoundIdentifier(vec![
+                    let (column_ident, data_type) = self.nested_column(
                         current_join_alias.clone(),
-                        self.column_ident(comparison_column_name),
-                    ]);
-                    Ok(ComparisonColumn::new_simple(
-                        column_ident,
-                        self.column_data_type(comparison_column_name, current_collection)?,
-                    ))
+                        comparison_column_name,
+                        field_path,
+                        current_collection,
+                    )?;
+                    Ok(ComparisonColumn::new_simple(column_ident, data_type))
                 }
             }
-            models::ComparisonTarget::RootCollectionColumn {
-                name,
-                field_path: _,
-            } => {
-                if current_is_origin {
-                    let column_ident = Expr::CompoundIdentifier(vec![
+            models::ComparisonTarget::RootCollectionColumn { name, field_path } => match root {
+                RootCollection::Current => {
+                    let (column_ident, data_type) = self.nested_column(
                         current_join_alias.clone(),
-                        self.column_ident(name),
-                    ]);
-                    Ok(ComparisonColumn::new_simple(
-                        column_ident,
-                        self.column_data_type(name, current_collection)?,
-                    ))
-                } else {
-                    Err(QueryBuilderError::NotSupported(
-                        "Comparisons to root".to_string(),
-                    ))
+                        name,
+                        field_path,
+                        current_collection,
+                    )?;
+                    Ok(ComparisonColumn::new_simple(column_ident, data_type))
                 }
-            }
+                RootCollection::Joined { alias, collection } => {
+                    let (column_ident, data_type) =
+                        self.nested_column(alias.clone(), name, field_path, collection)?;
+                    Ok(ComparisonColumn::new_simple(column_ident, data_type))
+                }
+                RootCollection::Unavailable => Err(QueryBuilderError::NotSupported(
+                    "Comparisons to root".to_string(),
+                )),
+            },
         }
     }
     fn collection_relationship(
@@ -1762,6 +2559,163 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
         collection: &CollectionContext,
         parameters: &mut ParameterBuilder,
     ) -> Result<TableFactor, QueryBuilderError> {
+        match &self.request.variables {
+            // arguments to parameterized views and native queries must be constants, so collections with variable arguments
+            // are expanded once per variable set, with a varset id column used to join the matching "_vars" row.
+            // the expansion is a common table expression, shared by all references to the same collection and arguments
+            Some(variables) if collection.has_variable_arguments() => {
+                if variables.is_empty() {
+                    return self.empty_collection(collection);
+                }
+
+                let arguments = collection.effective_arguments();
+
+                let existing_index =
+                    self.varset_collections
+                        .borrow()
+                        .iter()
+                        .position(|varset_collection| {
+                            &varset_collection.collection == collection.alias()
+                                && varset_collection.arguments == arguments
+                        });
+
+                if let Some(index) = existing_index {
+                    return Ok(
+                        ObjectName(vec![Ident::new_quoted(format!("_varsets_{index}"))])
+                            .into_table_factor(),
+                    );
+                }
+
+                let varset_id_column = self.varset_id_column(collection)?;
+
+                let varset_queries = variables
+                    .iter()
+                    .enumerate()
+                    .map(|(index, varset)| {
+                        let table =
+                            self.collection_table_factor(collection, Some(varset), parameters)?;
+                        Ok(Query::new()
+                            .select(vec![
+                                Expr::Value(Value::Number((index + 1).to_string()))
+                                    .into_select(Some(varset_id_column.clone())),
+                                SelectItem::Wildcard,
+                            ])
+                            .from(vec![table.into_table_with_joins(vec![])]))
+                    })
+                    .collect::<Result<Vec<_>, QueryBuilderError>>()?;
+
+                let mut varset_collections = self.varset_collections.borrow_mut();
+                let alias = format!("_varsets_{}", varset_collections.len());
+
+                varset_collections.push(VarsetCollection {
+                    collection: collection.alias().to_owned(),
+                    arguments,
+                    with_item: WithItem::union_all(varset_queries, &alias),
+                });
+
+                Ok(ObjectName(vec![Ident::new_quoted(alias)]).into_table_factor())
+            }
+            _ => self.collection_table_factor(collection, None, parameters),
+        }
+    }
+    /// A subquery with the columns of a collection but no rows.
+    /// Used in place of collections with variable arguments when the request has no variable sets
+    fn empty_collection(
+        &self,
+        collection: &CollectionContext,
+    ) -> Result<TableFactor, QueryBuilderError> {
+        let return_type = get_return_type(collection.alias(), self.configuration)?;
+        let table_type = self
+            .configuration
+            .table_types
+            .get(return_type)
+            .ok_or_else(|| QueryBuilderError::UnknownTableType(return_type.to_owned()))?;
+
+        let select = iter::once(
+            Expr::Value(Value::Null).into_select(Some(self.varset_id_column(collection)?)),
+        )
+        .chain(table_type.columns.iter().map(|(column_alias, data_type)| {
+            Function::new_unquoted("defaultValueOfTypeName")
+                .args(vec![Expr::Value(Value::SingleQuotedString(
+                    data_type.to_string(),
+                ))
+                .into_arg()])
+                .into_expr()
+                .into_select(Some(column_alias.to_string()))
+        }))
+        .collect();
+
+        Ok(Query::new()
+            .select(select)
+            .limit(Some(0))
+            .into_table_factor())
+    }
+    /// The column identifying the variable set of each row, for collections expanded once per variable set.
+    /// Underscores are prepended to "_varset_id" until it does not clash with a column of the collection
+    fn varset_id_column(&self, collection: &CollectionContext) -> Result<Ident, QueryBuilderError> {
+        let return_type = get_return_type(collection.alias(), self.configuration)?;
+        let table_type = self
+            .configuration
+            .table_types
+            .get(return_type)
+            .ok_or_else(|| QueryBuilderError::UnknownTableType(return_type.to_owned()))?;
+
+        let mut column_name = "_varset_id".to_string();
+        while table_type.columns.contains_key(column_name.as_str()) {
+            column_name.insert(0, '_');
+        }
+
+        Ok(Ident::new_quoted(column_name))
+    }
+    /// The join condition matching a collection to the current variable set,
+    /// for collections that were expanded once per variable set
+    fn varset_join_expr(
+        &self,
+        collection: &CollectionContext,
+        alias: &Ident,
+    ) -> Result<Option<Expr>, QueryBuilderError> {
+        if self.request.variables.is_some() && collection.has_variable_arguments() {
+            Ok(Some(Expr::BinaryOp {
+                left: Expr::CompoundIdentifier(vec![
+                    Ident::new_quoted("_vars"),
+                    Ident::new_quoted("_varset_id"),
+                ])
+                .into_box(),
+                op: BinaryOperator::Eq,
+                right: Expr::CompoundIdentifier(vec![
+                    alias.clone(),
+                    self.varset_id_column(collection)?,
+                ])
+                .into_box(),
+            }))
+        } else {
+            Ok(None)
+        }
+    }
+    /// The join operator joining a collection to "_vars"
+    fn vars_join_operator(
+        &self,
+        collection: &CollectionContext,
+        alias: &Ident,
+    ) -> Result<JoinOperator, QueryBuilderError> {
+        Ok(self
+            .varset_join_expr(collection, alias)?
+            .map(JoinConstraint::On)
+            .map(JoinOperator::Inner)
+            .unwrap_or(JoinOperator::CrossJoin))
+    }
+    fn collection_table_factor(
+        &self,
+        collection: &CollectionContext,
+        varset: Option<&BTreeMap<VariableName, serde_json::Value>>,
+        parameters: &mut ParameterBuilder,
+    ) -> Result<TableFactor, QueryBuilderError> {
+        let variable_value = |variable_name: &VariableName| {
+            varset
+                .and_then(|varset| varset.get(variable_name))
+                .ok_or_else(|| QueryBuilderError::MissingVariable(variable_name.to_owned()))
+        };
+
         if let Some(table) = self.configuration.tables.get(collection.alias()) {
             let table_argument_type = |argument_name: &ArgumentName| {
                 table.arguments.get(argument_name).ok_or_else(|| {
@@ -1771,25 +2725,6 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                     }
                 })
             };
-            let variable_argument = |arg_name: &ArgumentName, variable_name: &VariableName| {
-                let argument_type = table_argument_type(arg_name)?;
-                let column_ident = Expr::CompoundIdentifier(vec![
-                    Ident::new_quoted("_vars"),
-                    Ident::new_quoted(format!("_var_{variable_name}")),
-                ]);
-
-                // todo: review whether this is actually needed now, given we now have proper serialization of parameters. I think this is unrelated.
-                let column_ident = if is_uuid(argument_type) {
-                    Function::new_unquoted("toUUID")
-                        .args(vec![column_ident.into_arg()])
-                        .into_expr()
-                } else {
-                    column_ident
-                };
-                Ok(column_ident
-                    .into_arg()
-                    .name(Ident::new_quoted(arg_name.to_string())))
-            };
             let mut literal_argument = |arg_name: &ArgumentName, value: &serde_json::Value| {
                 Ok(parameters
                     .bind_json(value, table_argument_type(arg_name)?.to_owned().into())?
@@ -1809,7 +2744,7 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                         .iter()
                         .map(|(arg_name, arg)| match arg {
                             models::Argument::Variable { name } => {
-                                variable_argument(arg_name, name)
+                                literal_argument(arg_name, variable_value(name)?)
                             }
                             models::Argument::Literal { value } => {
                                 literal_argument(arg_name, value)
@@ -1825,14 +2760,14 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                         .chain(arguments.iter())
                         .map(|(arg_name, arg)| match arg {
                             models::RelationshipArgument::Variable { name } => {
-                                variable_argument(arg_name, name)
+                                literal_argument(arg_name, variable_value(name)?)
                             }
                             models::RelationshipArgument::Literal { value } => {
                                 literal_argument(arg_name, value)
                             }
                             models::RelationshipArgument::Column { .. } => {
                                 Err(QueryBuilderError::NotSupported(
-                                    "Column argument value".to_string(),
+                                    COLUMN_ARGUMENT_NOT_SUPPORTED.to_string(),
                                 ))
                             }
                         })
@@ -1844,14 +2779,14 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                         .iter()
                         .map(|(arg_name, arg)| match arg {
                             models::RelationshipArgument::Variable { name } => {
-                                variable_argument(arg_name, name)
+                                literal_argument(arg_name, variable_value(name)?)
                             }
                             models::RelationshipArgument::Literal { value } => {
                                 literal_argument(arg_name, value)
                             }
                             models::RelationshipArgument::Column { .. } => {
                                 Err(QueryBuilderError::NotSupported(
-                                    "Column argument value".to_string(),
+                                    COLUMN_ARGUMENT_NOT_SUPPORTED.to_string(),
                                 ))
                             }
                         })
@@ -1870,9 +2805,7 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                     collection_alias: _,
                     arguments,
                 } => arguments.get(name).map(|arg| match arg {
-                    models::Argument::Variable { .. } => Err(QueryBuilderError::NotSupported(
-                        "native query variable argument".to_string(),
-                    )),
+                    models::Argument::Variable { name } => variable_value(name),
                     models::Argument::Literal { value } => Ok(value),
                 }),
                 CollectionContext::Relationship {
@@ -1883,15 +2816,11 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                     .get(name)
                     .or_else(|| relationship_arguments.get(name))
                     .map(|arg| match arg {
-                        models::RelationshipArgument::Variable { .. } => {
-                            Err(QueryBuilderError::NotSupported(
-                                "native query variable argument".to_string(),
-                            ))
-                        }
+                        models::RelationshipArgument::Variable { name } => variable_value(name),
                         models::RelationshipArgument::Literal { value } => Ok(value),
                         models::RelationshipArgument::Column { .. } => {
                             Err(QueryBuilderError::NotSupported(
-                                "native query column argument".to_string(),
+                                COLUMN_ARGUMENT_NOT_SUPPORTED.to_string(),
                             ))
                         }
                     }),
@@ -1899,18 +2828,13 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                     collection_alias: _,
                     arguments,
                 } => arguments.get(name).map(|arg| match arg {
-                    models::RelationshipArgument::Variable { .. } => {
-                        Err(QueryBuilderError::NotSupported(
-                            "native query variable argument".to_string(),
-                        ))
-                    }
+                    models::RelationshipArgument::Variable { name } => variable_value(name),
                     models::RelationshipArgument::Literal { value } => Ok(value),
                     models::RelationshipArgument::Column { .. } => Err(
-                        QueryBuilderError::NotSupported("native query column argument".to_string()),
+                        QueryBuilderError::NotSupported(COLUMN_ARGUMENT_NOT_SUPPORTED.to_string()),
                     ),
                 }),
             };
-
             let elements = query
                 .query
                 .elements
@@ -1943,6 +2867,26 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
     fn column_ident(&self, column_alias: &FieldName) -> Ident {
         Ident::new_quoted(column_alias.to_string())
     }
+    /// The identifier of a column, qualified by the alias of its table unless building a mutation predicate
+    fn qualified_column_ident(&self, table_alias: &Ident, column_alias: &FieldName) -> Vec<Ident> {
+        if self.qualify_columns {
+            vec![table_alias.to_owned(), self.column_ident(column_alias)]
+        } else {
+            vec![self.column_ident(column_alias)]
+        }
+    }
+    /// Reference a variable in the current variable set.
+    /// The `_vars` table only exists when the request has variable sets, which is never the case for mutations
+    fn variable_ident(&self, name: &VariableName) -> Result<Expr, QueryBuilderError> {
+        if self.request.variables.is_none() {
+            return Err(QueryBuilderError::MissingVariable(name.to_owned()));
+        }
+
+        Ok(Expr::CompoundIdentifier(vec![
+            Ident::new_quoted("_vars"),
+            Ident::new_quoted(format!("_var_{name}")),
+        ]))
+    }
     fn column_data_type(
         &self,
         column_alias: &FieldName,
@@ -1973,10 +2917,69 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
 
         Ok(column_type.to_owned())
     }
+    /// An expression accessing a column, or a field nested in a column when a field path is given,
+    /// and the data type of the accessed value.
+    /// Fields nested in named tuples are accessed using `tupleElement`
+    fn nested_column(
+        &self,
+        table_alias: Ident,
+        column_alias: &FieldName,
+        field_path: &Option<Vec<FieldName>>,
+        collection: &CollectionContext,
+    ) -> Result<(Expr, ClickHouseDataType), QueryBuilderError> {
+        let data_type = self.column_data_type(column_alias, collection)?;
+        let mut column_ident = self.qualified_column_ident(&table_alias, column_alias);
+
+        let field_path = match field_path {
+            Some(field_path) if !field_path.is_empty() => field_path,
+            _ => return Ok((Expr::CompoundIdentifier(column_ident), data_type)),
+        };
+
+        // maps are scalars, so a field path into a map column is a key lookup
+        if let ClickHouseDataType::Map { .. } = data_type {
+            return map_key_lookup(
+                Expr::CompoundIdentifier(column_ident),
+                data_type,
+                field_path,
+            );
+        }
+
+        // json objects are scalars, so a field path into a json column selects the value at that path
+        if let ClickHouseDataType::Json(_) = data_type {
+            let path = field_path
+                .iter()
+                .map(|field_name| field_name.as_str())
+                .collect::<Vec<_>>();
+            let path_type = json_path_type(&data_type, &path);
+            column_ident.extend(
+                field_path
+                    .iter()
+                    .map(|field_name| Ident::new_quoted(field_name.to_string())),
+            );
+            return Ok((Expr::CompoundIdentifier(column_ident), path_type));
+        }
+
+        let return_type = get_return_type(collection.alias(), self.configuration)?;
+        let column_definition = ClickHouseTypeDefinition::from_table_column(
+            &data_type,
+            column_alias,
+            return_type,
+            &self.configuration.namespace_separator,
+        );
+
+        let type_definition = nested_field_definition(&column_definition, field_path)?;
+
+        Ok((
+            tuple_element_access(Expr::CompoundIdentifier(column_ident), field_path),
+            type_definition.cast_type(),
+        ))
+    }
+    #[allow(clippy::too_many_arguments)]
     fn column_accessor(
         &self,
         column_ident: Vec<Ident>,
         type_definition: &ClickHouseTypeDefinition,
+        source: RelationshipSource,
         traversed_array: bool,
         field_selector: Option<&models::NestedField>,
         rel_index: &mut u32,
@@ -2000,6 +3003,7 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                     if let Some((expr, joins)) = self.column_accessor(
                         vec![ident.clone()],
                         element_type,
+                        source,
                         true,
                         Some(&inner.fields),
                         rel_index,
@@ -2065,6 +3069,7 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                                 if let Some((expr, mut joins)) = self.column_accessor(
                                     column_ident.clone(),
                                     type_definition,
+                                    source,
                                     traversed_array,
                                     fields.as_ref(),
                                     rel_index,
@@ -2092,6 +3097,8 @@ impl<'r, 'c> QueryBuilder<'r, 'c> {
                                     alias,
                                     rel_index,
                                     &column_ident,
+                                    source,
+                                    Some(type_definition),
                                     query,
                                     relationship,
                                     arguments,
@@ -2147,11 +3154,245 @@ fn get_return_type<'a>(
         .ok_or_else(|| QueryBuilderError::UnknownTable(table_alias.to_owned()))
 }
 
+/// apply a built in aggregate function, or a parameterized aggregate function defined in configuration
 fn aggregate_function(
     name: &AggregateFunctionName,
-) -> Result<ClickHouseSingleColumnAggregateFunction, QueryBuilderError> {
-    ClickHouseSingleColumnAggregateFunction::from_str(name.inner())
-        .map_err(|_err| QueryBuilderError::UnknownSingleColumnAggregateFunction(name.to_owned()))
+    column: Expr,
+    configuration: &ServerConfig,
+) -> Result<Expr, QueryBuilderError> {
+    if let Ok(function) = ClickHouseSingleColumnAggregateFunction::from_str(name.inner()) {
+        Ok(apply_function(&function, column))
+    } else if let Some(function) = configuration.aggregate_functions.get(name) {
+        Ok(apply_parameterized_function(function, column))
+    } else {
+        Err(QueryBuilderError::UnknownSingleColumnAggregateFunction(
+            name.to_owned(),
+        ))
+    }
+}
+
+/// An expression accessing a field of a nested collection element, and the definition of that field.
+/// Only fields of the element itself may be referenced, not columns of related or root collections
+fn nested_collection_element_field<'a>(
+    element_ident: &Ident,
+    element_type: &'a ClickHouseTypeDefinition,
+    target: &models::ComparisonTarget,
+) -> Result<(Expr, &'a ClickHouseTypeDefinition), QueryBuilderError> {
+    let (name, field_path) = match target {
+        models::ComparisonTarget::Column {
+            name,
+            path,
+            field_path,
+        } if path.is_empty() => (name, field_path),
+        models::ComparisonTarget::Column { .. } => {
+            return Err(QueryBuilderError::NotSupported(
+                "Relationships in nested collection predicates".to_string(),
+            ))
+        }
+        models::ComparisonTarget::RootCollectionColumn { .. } => {
+            return Err(QueryBuilderError::NotSupported(
+                "Comparisons to root in nested collection predicates".to_string(),
+            ))
+        }
+    };
+
+    let field_path: Vec<_> = iter::once(name.to_owned())
+        .chain(field_path.iter().flatten().cloned())
+        .collect();
+    let type_definition = nested_field_definition(element_type, &field_path)?;
+
+    let field = tuple_element_access(element_ident.clone().into_expr(), &field_path);
+
+    Ok((field, type_definition))
+}
+
+/// Access a field nested in named tuples, such as `tupleElement(tupleElement("column", 'a'), 'b')`.
+/// Unlike dotted access, `tupleElement` cannot be mistaken for a reference to a table or column
+fn tuple_element_access(tuple: Expr, field_path: &[FieldName]) -> Expr {
+    field_path.iter().fold(tuple, |tuple, field_name| {
+        Function::new_unquoted("tupleElement")
+            .args(vec![
+                tuple.into_arg(),
+                Value::SingleQuotedString(field_name.to_string())
+                    .into_expr()
+                    .into_arg(),
+            ])
+            .into_expr()
+    })
+}
+
+/// The collection an exists predicate is evaluated from, see [`QueryBuilder::filter_exists_expression`]
+struct ExistsSource<'a, 'b, 'c> {
+    /// the alias of the collection in the outer query
+    join_alias: &'a Ident,
+    collection: &'a CollectionContext<'b, 'c>,
+    /// where root collection columns can be referenced from, relative to this collection
+    root: RootCollection<'a, 'b, 'c>,
+}
+
+/// A collection with variable arguments, expanded once per variable set, see [`QueryBuilder::collection_ident`]
+struct VarsetCollection {
+    collection: CollectionName,
+    arguments: BTreeMap<ArgumentName, models::RelationshipArgument>,
+    with_item: WithItem,
+}
+
+/// The collection relationship fields are selected from, see [`QueryBuilder::field_relationship`]
+#[derive(Debug, Clone, Copy)]
+struct RelationshipSource<'a, 'b, 'c> {
+    /// the alias of the source collection, which relationship source paths are rooted at
+    alias: &'a Ident,
+    collection: &'a CollectionContext<'b, 'c>,
+    /// the predicate of the source query, pushed down when fanning out from an array of keys
+    predicate: Option<&'a models::Expression>,
+}
+
+/// A join fanning a relationship out from an array of keys, see [`QueryBuilder::relationship_fan_out`]
+struct RelationshipFanOut {
+    join: Join,
+    /// the target column matched against the array elements
+    target_column: FieldName,
+    /// the array of keys, used in place of the target column as the relationship key
+    keys: Expr,
+}
+
+/// Arguments to parameterized views and native queries must be constants in ClickHouse,
+/// so they cannot take a different value for each parent row
+const COLUMN_ARGUMENT_NOT_SUPPORTED: &str =
+    "Column arguments to parameterized views and native queries";
+
+/// Collect the root collection columns referenced by an expression,
+/// including those referenced in the predicates of relationship paths and nested exists expressions
+fn root_collection_columns(expression: &models::Expression, columns: &mut Vec<FieldName>) {
+    match expression {
+        models::Expression::And { expressions } | models::Expression::Or { expressions } => {
+            for expression in expressions {
+                root_collection_columns(expression, columns)
+            }
+        }
+        models::Expression::Not { expression } => root_collection_columns(expression, columns),
+        models::Expression::UnaryComparisonOperator {
+            column,
+            operator: _,
+        } => comparison_target_root_columns(column, columns),
+        models::Expression::BinaryComparisonOperator {
+            column,
+            operator: _,
+            value,
+        } => {
+            comparison_target_root_columns(column, columns);
+            if let models::ComparisonValue::Column { column } = value {
+                comparison_target_root_columns(column, columns);
+            }
+        }
+        models::Expression::Exists {
+            in_collection,
+            predicate,
+        } => match in_collection {
+            // root references are not supported in nested collection predicates
+            models::ExistsInCollection::NestedCollection { .. } => {}
+            models::ExistsInCollection::Related { .. }
+            | models::ExistsInCollection::Unrelated { .. } => {
+                if let Some(predicate) = predicate {
+                    root_collection_columns(predicate, columns)
+                }
+            }
+        },
+    }
+}
+
+fn comparison_target_root_columns(target: &models::ComparisonTarget, columns: &mut Vec<FieldName>) {
+    match target {
+        models::ComparisonTarget::Column { path, .. } => {
+            for path_element in path {
+                if let Some(predicate) = &path_element.predicate {
+                    root_collection_columns(predicate, columns)
+                }
+            }
+        }
+        models::ComparisonTarget::RootCollectionColumn {
+            name,
+            field_path: _,
+        } => {
+            if !columns.contains(name) {
+                columns.push(name.to_owned())
+            }
+        }
+    }
+}
+
+/// Whether an expression compares against a variable, including in the predicates of relationship paths and exists expressions
+fn references_variables(expression: &models::Expression) -> bool {
+    match expression {
+        models::Expression::And { expressions } | models::Expression::Or { expressions } => {
+            expressions.iter().any(references_variables)
+        }
+        models::Expression::Not { expression } => references_variables(expression),
+        models::Expression::UnaryComparisonOperator {
+            column,
+            operator: _,
+        } => comparison_target_references_variables(column),
+        models::Expression::BinaryComparisonOperator {
+            column,
+            operator: _,
+            value,
+        } => {
+            comparison_target_references_variables(column)
+                || match value {
+                    models::ComparisonValue::Column { column } => {
+                        comparison_target_references_variables(column)
+                    }
+                    models::ComparisonValue::Scalar { .. } => false,
+                    models::ComparisonValue::Variable { .. } => true,
+                }
+        }
+        models::Expression::Exists {
+            in_collection: _,
+            predicate,
+        } => predicate
+            .as_ref()
+            .is_some_and(|predicate| references_variables(predicate)),
+    }
+}
+
+fn comparison_target_references_variables(target: &models::ComparisonTarget) -> bool {
+    match target {
+        models::ComparisonTarget::Column { path, .. } => path
+            .iter()
+            .filter_map(|path_element| path_element.predicate.as_ref())
+            .any(|predicate| references_variables(predicate)),
+        models::ComparisonTarget::RootCollectionColumn { .. } => false,
+    }
+}
+
+/// Walk a field path into nested object (named tuple) types, returning the definition of the innermost field
+pub(crate) fn nested_field_definition<'a>(
+    type_definition: &'a ClickHouseTypeDefinition,
+    field_path: &[FieldName],
+) -> Result<&'a ClickHouseTypeDefinition, QueryBuilderError> {
+    let mut type_definition = type_definition;
+
+    for field_name in field_path {
+        let fields = match type_definition.non_nullable() {
+            ClickHouseTypeDefinition::Object { name: _, fields } => fields,
+            _ => {
+                return Err(QueryBuilderError::ColumnTypeMismatch {
+                    expected: "Tuple/Object".to_string(),
+                    got: type_definition.cast_type().to_string(),
+                })
+            }
+        };
+
+        type_definition =
+            fields
+                .get(field_name)
+                .ok_or_else(|| QueryBuilderError::UnknownSubField {
+                    field_name: field_name.to_owned(),
+                    data_type: type_definition.cast_type(),
+                })?;
+    }
+
+    Ok(type_definition)
 }
 
 fn and_reducer(left: Expr, right: Expr) -> Expr {
@@ -2170,6 +3411,52 @@ fn or_reducer(left: Expr, right: Expr) -> Expr {
     }
 }
 
+/// look up a key in a map, such as `"column"['key']`, once for each key in the path for nested maps
+fn map_key_lookup(
+    map: Expr,
+    data_type: ClickHouseDataType,
+    keys: &[FieldName],
+) -> Result<(Expr, ClickHouseDataType), QueryBuilderError> {
+    keys.iter()
+        .try_fold((map, data_type), |(expr, data_type), key| match data_type {
+            ClickHouseDataType::Map {
+                key: key_type,
+                value: value_type,
+            } => {
+                let key = Value::SingleQuotedString(key.to_string()).into_expr();
+                // keys in field paths are strings, cast them to other key types
+                let key = match *key_type {
+                    key_type if is_string(&key_type) => key,
+                    key_type => Function::new_unquoted("CAST")
+                        .args(vec![
+                            key.into_arg(),
+                            Value::SingleQuotedString(key_type.to_string())
+                                .into_expr()
+                                .into_arg(),
+                        ])
+                        .into_expr(),
+                };
+                let expr = Expr::Subscript {
+                    expr: expr.into_box(),
+                    subscript: key.into_box(),
+                };
+                Ok((expr, *value_type))
+            }
+            _ => Err(QueryBuilderError::ColumnTypeMismatch {
+                expected: "Map".to_string(),
+                got: data_type.to_string(),
+            }),
+        })
+}
+
+fn is_string(t: &ClickHouseDataType) -> bool {
+    match t {
+        ClickHouseDataType::Nullable(t) | ClickHouseDataType::LowCardinality(t) => is_string(t),
+        ClickHouseDataType::String | ClickHouseDataType::FixedString(_) => true,
+        _ => false,
+    }
+}
+
 fn is_uuid(t: &ClickHouseDataType) -> bool {
     match t {
         ClickHouseDataType::Nullable(t) => is_uuid(t),
@@ -1,5 +1,39 @@
-use crate::sql::ast::{BinaryOperator, Expr, Function};
-use common::schema::binary_comparison_operator::ClickHouseBinaryComparisonOperator;
+use crate::sql::ast::{BinaryOperator, Expr, Function, Value};
+use common::{
+    clickhouse_parser::datatype::{ClickHouseDataType, Identifier},
+    schema::binary_comparison_operator::ClickHouseBinaryComparisonOperator,
+};
+
+/// The data type of the right hand side of a comparison, derived from the data type of the left hand side.
+/// This is the left hand type, except for IN/NOT IN where it is Array(<left hand data type>),
+/// and for operators on map columns, which take a key or an array of keys,
+/// and for geo operators, which take a polygon, or a point and a distance
+pub fn comparison_argument_type(
+    operator: &ClickHouseBinaryComparisonOperator,
+    left_type: ClickHouseDataType,
+) -> ClickHouseDataType {
+    use ClickHouseBinaryComparisonOperator as CBO;
+
+    match (operator, left_type) {
+        (CBO::In | CBO::NotIn, left_type) => ClickHouseDataType::Array(Box::new(left_type)),
+        (CBO::HasKey, ClickHouseDataType::Map { key, value: _ }) => *key,
+        (CBO::HasAnyKey | CBO::HasAllKeys, ClickHouseDataType::Map { key, value: _ }) => {
+            ClickHouseDataType::Array(key)
+        }
+        (CBO::WithinPolygon, _) => ClickHouseDataType::Polygon,
+        (CBO::WithinDistance, _) => ClickHouseDataType::Tuple(vec![
+            (
+                Some(Identifier::Unquoted("point".to_string())),
+                ClickHouseDataType::Point,
+            ),
+            (
+                Some(Identifier::Unquoted("distance".to_string())),
+                ClickHouseDataType::Float64,
+            ),
+        ]),
+        (_, left_type) => left_type,
+    }
+}
 
 pub fn apply_binary_operator(
     operator: &ClickHouseBinaryComparisonOperator,
@@ -18,6 +52,26 @@ pub fn apply_binary_operator(
             .args(vec![left.into_arg(), right.into_arg()])
             .into_expr()
     }
+    fn apply_position_function(name: &str, left: Expr, right: Expr) -> Expr {
+        // position functions return the 1-based index of the substring, or 0 if not found
+        apply_operator(
+            BinaryOperator::Gt,
+            apply_function(name, left, right),
+            Expr::Value(Value::Number("0".to_string())),
+        )
+    }
+    fn tuple_element(tuple: Expr, index: u32) -> Expr {
+        apply_function(
+            "tupleElement",
+            tuple,
+            Expr::Value(Value::Number(index.to_string())),
+        )
+    }
+    fn map_keys(map: Expr) -> Expr {
+        Function::new_unquoted("mapKeys")
+            .args(vec![map.into_arg()])
+            .into_expr()
+    }
     use ClickHouseBinaryComparisonOperator as CBO;
 
     match operator {
@@ -34,5 +88,27 @@ pub fn apply_binary_operator(
         CBO::In => apply_operator(BinaryOperator::In, left, right),
         CBO::NotIn => apply_operator(BinaryOperator::NotIn, left, right),
         CBO::Match => apply_function("match", left, right),
+        CBO::Contains => apply_position_function("position", left, right),
+        CBO::IContains => apply_position_function("positionCaseInsensitive", left, right),
+        CBO::StartsWith => apply_function("startsWith", left, right),
+        CBO::EndsWith => apply_function("endsWith", left, right),
+        CBO::HasToken => apply_function("hasToken", left, right),
+        CBO::HasKey => apply_function("mapContains", left, right),
+        CBO::HasAnyKey => apply_function("hasAny", map_keys(left), right),
+        CBO::HasAllKeys => apply_function("hasAll", map_keys(left), right),
+        CBO::WithinPolygon => apply_function("pointInPolygon", left, right),
+        CBO::WithinDistance => {
+            // the argument is a tuple of a point and a distance in meters, points are (longitude, latitude)
+            let point = tuple_element(right.clone(), 1);
+            let distance = Function::new_unquoted("greatCircleDistance")
+                .args(vec![
+                    tuple_element(left.clone(), 1).into_arg(),
+                    tuple_element(left, 2).into_arg(),
+                    tuple_element(point.clone(), 1).into_arg(),
+                    tuple_element(point, 2).into_arg(),
+                ])
+                .into_expr();
+            apply_operator(BinaryOperator::LtEq, distance, tuple_element(right, 2))
+        }
     }
 }
@@ -2,6 +2,8 @@ use std::collections::BTreeMap;
 
 use ndc_models::{Argument, ArgumentName, CollectionName, Relationship, RelationshipArgument};
 
+use crate::sql::ast::Ident;
+
 #[derive(Debug, Clone)]
 pub enum CollectionContext<'a, 'b> {
     Base {
@@ -78,4 +80,83 @@ impl<'a, 'b> CollectionContext<'a, 'b> {
             } => !arguments.is_empty(),
         }
     }
+    /// The arguments the collection is called with, with field and path arguments taking precedence over relationship arguments
+    pub fn effective_arguments(&self) -> BTreeMap<ArgumentName, RelationshipArgument> {
+        match self {
+            CollectionContext::Base {
+                collection_alias: _,
+                arguments,
+            } => arguments
+                .iter()
+                .map(|(name, argument)| {
+                    let argument = match argument {
+                        Argument::Variable { name } => RelationshipArgument::Variable {
+                            name: name.to_owned(),
+                        },
+                        Argument::Literal { value } => RelationshipArgument::Literal {
+                            value: value.to_owned(),
+                        },
+                    };
+                    (name.to_owned(), argument)
+                })
+                .collect(),
+            CollectionContext::Relationship {
+                collection_alias: _,
+                arguments,
+                relationship_arguments,
+            } => relationship_arguments
+                .iter()
+                .chain(arguments.iter())
+                .map(|(name, argument)| (name.to_owned(), argument.to_owned()))
+                .collect(),
+            CollectionContext::UnrelatedRelationship {
+                collection_alias: _,
+                arguments,
+            } => (*arguments).to_owned(),
+        }
+    }
+    pub fn has_variable_arguments(&self) -> bool {
+        match self {
+            CollectionContext::Base {
+                collection_alias: _,
+                arguments,
+            } => arguments
+                .values()
+                .any(|argument| matches!(argument, Argument::Variable { .. })),
+            CollectionContext::Relationship {
+                collection_alias: _,
+                arguments,
+                relationship_arguments,
+            } => arguments
+                .values()
+                .chain(relationship_arguments.values())
+                .any(|argument| matches!(argument, RelationshipArgument::Variable { .. })),
+            CollectionContext::UnrelatedRelationship {
+                collection_alias: _,
+                arguments,
+            } => arguments
+                .values()
+                .any(|argument| matches!(argument, RelationshipArgument::Variable { .. })),
+        }
+    }
+}
+
+/// Where columns of the root collection can be referenced from, relative to the expression being compiled
+#[derive(Debug, Clone, Copy)]
+pub enum RootCollection<'a, 'b, 'c> {
+    /// The current collection is the root collection
+    Current,
+    /// The root collection is joined under the given alias, for example inside exists subqueries
+    Joined {
+        alias: &'a Ident,
+        collection: &'a CollectionContext<'b, 'c>,
+    },
+    /// The root collection cannot be referenced
+    Unavailable,
+}
+
+impl<'a, 'b, 'c> RootCollection<'a, 'b, 'c> {
+    pub fn is_current(&self) -> bool {
+        matches!(self, RootCollection::Current)
+    }
 }
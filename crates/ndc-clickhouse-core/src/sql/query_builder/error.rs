@@ -3,7 +3,7 @@ use common::clickhouse_parser::{datatype::ClickHouseDataType, parameterized_quer
 use http::StatusCode;
 use ndc_models::{
     AggregateFunctionName, ArgumentName, CollectionName, ComparisonOperatorName, FieldName,
-    ObjectTypeName, RelationshipName,
+    ObjectTypeName, ProcedureName, RelationshipName, VariableName,
 };
 use ndc_sdk_core::connector::ErrorResponse;
 
@@ -18,6 +18,28 @@ pub enum QueryBuilderError {
         query: CollectionName,
         argument: ArgumentName,
     },
+    /// A procedure was referenced but not found in configuration
+    #[error("Unable to find procedure {0} in config")]
+    UnknownProcedure(ProcedureName),
+    /// An argument required for a procedure was not supplied
+    #[error("Argument {argument} required for procedure {procedure} was not supplied")]
+    MissingProcedureArgument {
+        procedure: ProcedureName,
+        argument: ArgumentName,
+    },
+    /// An argument was supplied for a procedure that does not have that argument
+    #[error("Unknown argument {argument} supplied for procedure {procedure}")]
+    UnknownProcedureArgument {
+        procedure: ProcedureName,
+        argument: ArgumentName,
+    },
+    /// An argument supplied for a procedure did not have the expected shape
+    #[error("Invalid argument {argument} supplied for procedure {procedure}: {message}")]
+    InvalidProcedureArgument {
+        procedure: ProcedureName,
+        argument: ArgumentName,
+        message: String,
+    },
     /// A table was referenced but not found in configuration
     #[error("Unable to find table {0} in config")]
     UnknownTable(CollectionName),
@@ -45,6 +67,9 @@ pub enum QueryBuilderError {
         field_name: FieldName,
         data_type: ClickHouseDataType,
     },
+    /// A variable was referenced but not supplied in a variable set
+    #[error("Variable {0} was not supplied")]
+    MissingVariable(VariableName),
     /// Unable to serialize variables into a json string
     #[error("Unable to serialize variables into a json string: {0}")]
     CannotSerializeVariables(String),
@@ -104,12 +129,17 @@ impl From<QueryBuilderError> for ErrorResponse {
         match value {
             QueryBuilderError::MissingRelationship(_)
             | QueryBuilderError::MissingNativeQueryArgument { .. }
+            | QueryBuilderError::UnknownProcedure(_)
+            | QueryBuilderError::MissingProcedureArgument { .. }
+            | QueryBuilderError::UnknownProcedureArgument { .. }
+            | QueryBuilderError::InvalidProcedureArgument { .. }
             | QueryBuilderError::UnknownTable(_)
             | QueryBuilderError::UnknownTableArgument { .. }
             | QueryBuilderError::UnknownQueryArgument { .. }
             | QueryBuilderError::UnknownTableType(_)
             | QueryBuilderError::UnknownColumn(_, _)
             | QueryBuilderError::UnknownSubField { .. }
+            | QueryBuilderError::MissingVariable(_)
             | QueryBuilderError::CannotSerializeVariables(_)
             | QueryBuilderError::UnknownSingleColumnAggregateFunction(_)
             | QueryBuilderError::UnknownBinaryComparisonOperator(_)
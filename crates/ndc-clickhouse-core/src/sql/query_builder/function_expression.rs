@@ -1,5 +1,8 @@
-use crate::sql::ast::{Expr, Function};
-use common::schema::single_column_aggregate_function::ClickHouseSingleColumnAggregateFunction;
+use crate::sql::ast::{Expr, Function, Value};
+use common::{
+    config_file::ParameterizedAggregateFunction,
+    schema::single_column_aggregate_function::ClickHouseSingleColumnAggregateFunction,
+};
 
 pub fn apply_function(function: &ClickHouseSingleColumnAggregateFunction, column: Expr) -> Expr {
     use ClickHouseSingleColumnAggregateFunction::*;
@@ -17,5 +20,42 @@ pub fn apply_function(function: &ClickHouseSingleColumnAggregateFunction, column
         StddevSamp => sql_fn("stddevSamp", column),
         VarPop => sql_fn("varPop", column),
         VarSamp => sql_fn("varSamp", column),
+        Median => sql_fn("median", column),
+        Quantile50 => parametric_fn("quantile", "0.5".to_string(), column),
+        Quantile90 => parametric_fn("quantile", "0.9".to_string(), column),
+        Quantile95 => parametric_fn("quantile", "0.95".to_string(), column),
+        Quantile99 => parametric_fn("quantile", "0.99".to_string(), column),
+        Uniq => sql_fn("uniq", column),
+        UniqExact => sql_fn("uniqExact", column),
+        Any => sql_fn("any", column),
+        AnyLast => sql_fn("anyLast", column),
+        GroupArray => sql_fn("groupArray", column),
+        GroupUniqArray => sql_fn("groupUniqArray", column),
+        TopK => sql_fn("topK", column),
+        // false orders before true, so the logical and/or of Bool values is their min/max.
+        // the OrNull combinator returns NULL when there are no non-null values to aggregate,
+        // instead of the default value false, which would be wrong for bool_and
+        BoolAnd => sql_fn("minOrNull", column),
+        BoolOr => sql_fn("maxOrNull", column),
     }
 }
+
+pub fn apply_parameterized_function(
+    function: &ParameterizedAggregateFunction,
+    column: Expr,
+) -> Expr {
+    match function {
+        ParameterizedAggregateFunction::Quantile { level } => {
+            parametric_fn("quantile", level.to_string(), column)
+        }
+        ParameterizedAggregateFunction::TopK { k } => parametric_fn("topK", k.to_string(), column),
+    }
+}
+
+/// a parametric aggregate function with a single numeric parameter, such as `quantile(0.5)(column)`
+fn parametric_fn(name: &str, parameter: String, arg: Expr) -> Expr {
+    Function::new_unquoted(name)
+        .parameters(vec![Expr::Value(Value::Number(parameter)).into_arg()])
+        .args(vec![arg.into_arg()])
+        .into_expr()
+}
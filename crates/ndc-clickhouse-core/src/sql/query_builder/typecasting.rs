@@ -1,19 +1,16 @@
 use common::{
-    clickhouse_parser::datatype::{ClickHouseDataType, Identifier},
+    clickhouse_parser::datatype::{ClickHouseDataType, Identifier, JsonTypeParameter},
     config::ServerConfig,
-    schema::{
-        single_column_aggregate_function::ClickHouseSingleColumnAggregateFunction,
-        type_definition::ClickHouseTypeDefinition,
-    },
+    schema::type_definition::{ClickHouseTypeDefinition, JSON_PATH_ARGUMENT, MAP_KEY_ARGUMENT},
 };
 use indexmap::IndexMap;
 use ndc_models::{
-    self as models, AggregateFunctionName, CollectionName, FieldName, NestedField, ObjectTypeName,
-    RelationshipName,
+    self as models, AggregateFunctionName, ArgumentName, CollectionName, FieldName, NestedField,
+    ObjectTypeName, RelationshipName,
 };
-use std::{collections::BTreeMap, str::FromStr};
+use std::collections::BTreeMap;
 
-use super::QueryBuilderError;
+use super::{nested_field_definition, QueryBuilderError};
 
 /// Tuple(rows <RowsCastString>, aggregates <RowsCastString>)
 pub struct RowsetTypeString {
@@ -104,31 +101,30 @@ impl AggregatesTypeString {
                     models::Aggregate::SingleColumn {
                         column: column_alias,
                         function,
-                        field_path: _,
+                        field_path,
                     } => {
                         let return_type = get_return_type(table_alias, config)?;
                         let column_type = get_column(column_alias, return_type, config)?;
-                        let type_definition = ClickHouseTypeDefinition::from_table_column(
+                        let column_definition = ClickHouseTypeDefinition::from_table_column(
                             column_type,
                             column_alias,
                             return_type,
                             &config.namespace_separator,
                         );
+                        let type_definition = nested_field_definition(
+                            &column_definition,
+                            field_path.as_deref().unwrap_or_default(),
+                        )
+                        .map_err(|err| TypeStringError::NestedField(Box::new(err)))?;
+                        let column_type = &type_definition.cast_type();
 
-                        let aggregate_function =
-                            ClickHouseSingleColumnAggregateFunction::from_str(function.inner())
-                                .map_err(|_err| TypeStringError::UnknownAggregateFunction {
-                                    table: table_alias.to_owned(),
-                                    column: column_alias.to_owned(),
-                                    data_type: column_type.to_owned(),
-                                    function: function.to_owned(),
-                                })?;
-
-                        let aggregate_functions = type_definition.aggregate_functions();
+                        // includes the parameterized aggregate functions defined in configuration
+                        let aggregate_functions =
+                            type_definition.aggregate_functions(&config.aggregate_functions);
 
                         let result_type = aggregate_functions
                             .iter()
-                            .find(|(function, _)| function == &aggregate_function)
+                            .find(|(function_name, _)| function_name == function)
                             .map(|(_, result_type)| result_type)
                             .ok_or_else(|| TypeStringError::UnknownAggregateFunction {
                                 table: table_alias.to_owned(),
@@ -177,10 +173,36 @@ impl RowTypeString {
                             models::Field::Column {
                                 column: column_alias,
                                 fields,
-                                arguments: _,
+                                arguments,
                             } => {
                                 let return_type = get_return_type(table_alias, config)?;
                                 let column_type = get_column(column_alias, return_type, config)?;
+
+                                if let Some((_, value_type, _)) =
+                                    map_key_argument(column_type, arguments)
+                                {
+                                    return Ok((
+                                        alias.to_owned(),
+                                        FieldTypeString::Scalar(value_type.to_owned()),
+                                    ));
+                                }
+
+                                if let Some(path) = json_path_argument(column_type, arguments) {
+                                    let path_type = match path {
+                                        models::Argument::Literal {
+                                            value: serde_json::Value::String(path),
+                                        } => json_path_type(
+                                            column_type,
+                                            &path.split('.').collect::<Vec<_>>(),
+                                        ),
+                                        _ => ClickHouseDataType::Dynamic { max_types: None },
+                                    };
+                                    return Ok((
+                                        alias.to_owned(),
+                                        FieldTypeString::Scalar(path_type),
+                                    ));
+                                }
+
                                 let type_definition = ClickHouseTypeDefinition::from_table_column(
                                     column_type,
                                     column_alias,
@@ -390,6 +412,77 @@ impl FieldTypeString {
     }
 }
 
+/// The key argument of a map column, when the field returns the value for a single key instead of the whole map.
+/// Returns the key and value types of the map, and the key argument.
+/// A null key returns the whole map
+pub fn map_key_argument<'a>(
+    data_type: &'a ClickHouseDataType,
+    arguments: &'a BTreeMap<ArgumentName, models::Argument>,
+) -> Option<(
+    &'a ClickHouseDataType,
+    &'a ClickHouseDataType,
+    &'a models::Argument,
+)> {
+    let (key_type, value_type) = match data_type {
+        ClickHouseDataType::Map { key, value } => (&**key, &**value),
+        _ => return None,
+    };
+
+    match arguments.get(&ArgumentName::from(MAP_KEY_ARGUMENT)) {
+        None
+        | Some(models::Argument::Literal {
+            value: serde_json::Value::Null,
+        }) => None,
+        Some(argument) => Some((key_type, value_type, argument)),
+    }
+}
+
+/// The path argument of a JSON column, when the field returns the value at a path instead of the whole object.
+/// A null path returns the whole object
+pub fn json_path_argument<'a>(
+    data_type: &ClickHouseDataType,
+    arguments: &'a BTreeMap<ArgumentName, models::Argument>,
+) -> Option<&'a models::Argument> {
+    if !matches!(data_type, ClickHouseDataType::Json(_)) {
+        return None;
+    }
+
+    match arguments.get(&ArgumentName::from(JSON_PATH_ARGUMENT)) {
+        None
+        | Some(models::Argument::Literal {
+            value: serde_json::Value::Null,
+        }) => None,
+        Some(argument) => Some(argument),
+    }
+}
+
+/// The data type of the value at a path in a JSON column.
+/// Typed paths have their declared type, any other path is Dynamic
+pub fn json_path_type(data_type: &ClickHouseDataType, path: &[&str]) -> ClickHouseDataType {
+    let parameters = match data_type {
+        ClickHouseDataType::Json(parameters) => parameters,
+        _ => return ClickHouseDataType::Dynamic { max_types: None },
+    };
+
+    parameters
+        .iter()
+        .find_map(|parameter| match parameter {
+            JsonTypeParameter::TypedPath {
+                path: typed_path,
+                data_type,
+            } if typed_path.len() == path.len()
+                && typed_path
+                    .iter()
+                    .zip(path)
+                    .all(|(typed, element)| typed.value() == *element) =>
+            {
+                Some(data_type.to_owned())
+            }
+            _ => None,
+        })
+        .unwrap_or(ClickHouseDataType::Dynamic { max_types: None })
+}
+
 fn get_column<'a>(
     column_alias: &FieldName,
     return_type: &ObjectTypeName,
@@ -463,6 +556,8 @@ pub enum TypeStringError {
         field_name: FieldName,
         object_type: ObjectTypeName,
     },
+    #[error("{0}")]
+    NestedField(Box<QueryBuilderError>),
 }
 
 impl From<TypeStringError> for QueryBuilderError {
@@ -0,0 +1,194 @@
+use common::{
+    config::{read_server_config, ConfigurationEnvironment, ConfigurationError, ServerConfig},
+    schema::schema_response,
+};
+use insta::{assert_snapshot, assert_yaml_snapshot, glob};
+use ndc_clickhouse_core::{
+    connector::handler::mutation_explain_details,
+    sql::{ast::MutationStatement, MutationBuilder},
+};
+use ndc_models as models;
+use std::{collections::HashMap, fs, path::PathBuf};
+
+fn base_path() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("mutation_builder")
+}
+
+async fn read_mock_configuration(config_dir: PathBuf) -> Result<ServerConfig, ConfigurationError> {
+    // set mock values for required env vars, we won't be reading these anyways
+    let env = HashMap::from_iter(vec![
+        ("CLICKHOUSE_URL".to_owned(), "".to_owned()),
+        ("CLICKHOUSE_USERNAME".to_owned(), "".to_owned()),
+        ("CLICKHOUSE_PASSWORD".to_owned(), "".to_owned()),
+    ]);
+    read_server_config(
+        config_dir.as_path(),
+        &ConfigurationEnvironment::from_simulated_environment(env),
+    )
+    .await
+}
+
+fn pretty_print_sql(query: &str) -> String {
+    use sqlformat::{format, FormatOptions, Indent, QueryParams};
+    let params = QueryParams::None;
+    let options = FormatOptions {
+        indent: Indent::Spaces(2),
+        uppercase: false,
+        lines_between_queries: 1,
+    };
+
+    format(query, &params, options)
+}
+
+fn print_statement(statement: &MutationStatement) -> String {
+    match statement {
+        // insert statements are followed by the rows to insert, which should not be formatted as SQL
+        MutationStatement::Insert(_) => statement.to_string(),
+        _ => pretty_print_sql(&statement.to_string()),
+    }
+}
+
+fn print_parameters(parameters: Vec<(String, String)>) -> String {
+    parameters
+        .into_iter()
+        .fold(String::new(), |mut acc, (name, value)| {
+            acc.reserve(name.len() + value.len() + 2);
+            acc.push_str(&name);
+            acc.push('=');
+            acc.push_str(&value);
+            acc.push('\n');
+            acc
+        })
+}
+
+#[tokio::test]
+async fn test_sql_generation() {
+    for schema_dir in ["events"] {
+        let configuration = read_mock_configuration(base_path().join(schema_dir).join("_config"))
+            .await
+            .expect("Should be able to read configuration");
+
+        glob!(
+            base_path().join(schema_dir),
+            "*.request.json",
+            |file_path| {
+                let file = fs::read_to_string(file_path).expect("Should read request file");
+                let request: models::MutationRequest =
+                    serde_json::from_str(&file).expect("File should be valid mutation request");
+
+                let [operation] = request.operations.as_slice() else {
+                    panic!("Mutation request fixtures should have exactly one operation")
+                };
+
+                let builder = MutationBuilder::new(
+                    operation,
+                    &request.collection_relationships,
+                    &configuration,
+                );
+
+                let inlined_sql = match builder.build_inlined() {
+                    Err(err) => {
+                        assert_snapshot!(format!("{schema_dir} Expected Error"), err);
+                        return;
+                    }
+                    Ok(inlined_sql) => print_statement(&inlined_sql),
+                };
+
+                assert_snapshot!(format!("{schema_dir} Inlined SQL"), inlined_sql);
+
+                let (parameterized_sql, parameters) = builder
+                    .build_parameterized()
+                    .expect("Should build parameterized SQL");
+                let parameterized_sql = print_statement(&parameterized_sql);
+
+                // settings are passed as parameters, so unlike queries, mutations may have parameters but no placeholders
+                if parameterized_sql != inlined_sql {
+                    assert_snapshot!(format!("{schema_dir} Parameterized SQL"), parameterized_sql);
+                }
+                assert_snapshot!(
+                    format!("{schema_dir} Parameters"),
+                    print_parameters(parameters)
+                );
+            }
+        )
+    }
+}
+
+#[tokio::test]
+async fn test_schemas() {
+    for schema_dir in ["events"] {
+        let configuration = read_mock_configuration(base_path().join(schema_dir).join("_config"))
+            .await
+            .expect("Should be able to read configuration");
+
+        let schema = schema_response(&configuration);
+        assert_yaml_snapshot!(format!("{schema_dir} Schema Response"), schema);
+    }
+}
+
+#[tokio::test]
+async fn test_mutation_explain() {
+    let configuration = read_mock_configuration(base_path().join("events").join("_config"))
+        .await
+        .expect("Should be able to read configuration");
+
+    let read_request = |file_name: &str| -> models::MutationRequest {
+        let file = fs::read_to_string(base_path().join("events").join(file_name))
+            .expect("Should read request file");
+        serde_json::from_str(&file).expect("File should be valid mutation request")
+    };
+
+    // enough operations that indexes have more than one digit.
+    // Inserts, deletes and updates have no execution plan
+    let insert = read_request("01_insert_01_rows.request.json");
+    let native_query = read_request("02_native_query_01_procedure.request.json");
+    let delete = read_request("03_delete_01_predicate.request.json");
+    let update = read_request("04_update_01_set.request.json");
+    let request = models::MutationRequest {
+        operations: insert
+            .operations
+            .iter()
+            .chain(native_query.operations.iter().cycle().take(8))
+            .chain(delete.operations.iter())
+            .chain(update.operations.iter())
+            .cloned()
+            .collect(),
+        collection_relationships: native_query.collection_relationships,
+    };
+
+    let explain = mutation_explain_details(&configuration, &request)
+        .expect("Should explain mutation request");
+
+    let keys = explain.details.keys().cloned().collect::<Vec<_>>();
+    assert_yaml_snapshot!("events Mutation Explain Keys", keys);
+
+    let execution_plan_keys = explain
+        .execution_plans
+        .iter()
+        .map(|plan| plan.key.to_owned())
+        .collect::<Vec<_>>();
+    assert_yaml_snapshot!(
+        "events Mutation Explain Execution Plan Keys",
+        execution_plan_keys
+    );
+}
+
+#[tokio::test]
+async fn test_procedure_name_conflicts() {
+    let config_dir = base_path().join("_invalid").join("procedure_name_conflict");
+
+    match read_mock_configuration(config_dir).await {
+        Err(ConfigurationError::ValidateError {
+            node_path, message, ..
+        }) => {
+            assert_eq!(node_path, ["queries", "insert_events"]);
+            assert_eq!(
+                message,
+                "Name conflicts with a procedure generated for table events"
+            );
+        }
+        other => panic!("Expected a validation error, got {other:?}"),
+    }
+}
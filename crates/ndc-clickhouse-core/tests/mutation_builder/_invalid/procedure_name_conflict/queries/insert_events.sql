@@ -0,0 +1 @@
+INSERT INTO analytics.events (id, name) VALUES ({id: UUID}, {name: String})
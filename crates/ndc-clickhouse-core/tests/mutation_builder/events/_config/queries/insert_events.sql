@@ -0,0 +1,3 @@
+SELECT *
+FROM "analytics"."events"
+WHERE "name" = 'insert'
@@ -0,0 +1,5 @@
+INSERT INTO "analytics"."daily_events" ("day", "name", "event_count")
+SELECT toDate("created_at") AS "day", "name", count() AS "event_count"
+FROM "analytics"."events"
+WHERE "created_at" >= {since: DateTime64(3)} AND "created_at" < {until: DateTime64(3)}
+GROUP BY "day", "name"
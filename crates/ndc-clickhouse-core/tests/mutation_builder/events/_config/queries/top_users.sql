@@ -0,0 +1,6 @@
+SELECT "user_id", count() AS "event_count"
+FROM "analytics"."events"
+WHERE "created_at" >= {since: DateTime64(3)}
+GROUP BY "user_id"
+ORDER BY "event_count" DESC
+LIMIT {limit: UInt32}
@@ -1,5 +1,5 @@
 use common::{
-    config::{read_server_config, ConfigurationEnvironment, ServerConfig},
+    config::{read_server_config, ConfigurationEnvironment, ConfigurationError, ServerConfig},
     config_file::ServerConfigFile,
     schema::schema_response,
 };
@@ -45,7 +45,13 @@ fn pretty_print_sql(query: &str) -> String {
 
 #[tokio::test]
 async fn test_sql_generation() {
-    for schema_dir in ["chinook", "complex_columns", "star_schema"] {
+    for schema_dir in [
+        "chinook",
+        "column_types",
+        "complex_columns",
+        "parameterized_collections",
+        "star_schema",
+    ] {
         let configuration = read_mock_configuration(schema_dir).await;
 
         glob!(
@@ -100,7 +106,13 @@ async fn test_sql_generation() {
 
 #[tokio::test]
 async fn test_schemas() {
-    for schema_dir in ["chinook", "complex_columns", "star_schema"] {
+    for schema_dir in [
+        "chinook",
+        "column_types",
+        "complex_columns",
+        "parameterized_collections",
+        "star_schema",
+    ] {
         let configuration = read_mock_configuration(schema_dir).await;
 
         let schema = schema_response(&configuration);
@@ -108,6 +120,34 @@ async fn test_schemas() {
     }
 }
 
+#[tokio::test]
+async fn test_point_distance_type_conflict() {
+    let env = HashMap::from_iter(vec![
+        ("CLICKHOUSE_URL".to_owned(), "".to_owned()),
+        ("CLICKHOUSE_USERNAME".to_owned(), "".to_owned()),
+        ("CLICKHOUSE_PASSWORD".to_owned(), "".to_owned()),
+    ]);
+    let config_dir = base_path().join("_invalid").join("point_distance_conflict");
+
+    match read_server_config(
+        config_dir.as_path(),
+        &ConfigurationEnvironment::from_simulated_environment(env),
+    )
+    .await
+    {
+        Err(ConfigurationError::ValidateError {
+            node_path, message, ..
+        }) => {
+            assert_eq!(node_path, ["tables", "PointDistance"]);
+            assert_eq!(
+                message,
+                "Name conflicts with the argument type of the _within_distance operator"
+            );
+        }
+        other => panic!("Expected a validation error, got {other:?}"),
+    }
+}
+
 #[tokio::test]
 #[ignore]
 async fn update_json_schema() -> Result<(), Box<dyn Error>> {
@@ -0,0 +1,3 @@
+SELECT "id", "user_id"
+FROM "analytics"."sessions"
+WHERE "started_at" >= {since: DateTime}